@@ -0,0 +1,26 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use we_snark::mul_snark::{bench_setup, mul_prove};
+use we_snark::verifier::{PreparedLVDigest, lv_verify, lv_verify_prepared};
+
+fn bench_prove(c: &mut Criterion) {
+    let (crs, dg, w) = bench_setup();
+    c.bench_function("mul_prove", |b| b.iter(|| mul_prove(&crs, &dg, black_box(&w))));
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let (crs, dg, w) = bench_setup();
+    let pi = mul_prove(&crs, &dg, &w);
+    c.bench_function("lv_verify", |b| b.iter(|| lv_verify(&crs, &dg.lv, black_box(&pi.lv))));
+}
+
+fn bench_verify_prepared(c: &mut Criterion) {
+    let (crs, dg, w) = bench_setup();
+    let pi = mul_prove(&crs, &dg, &w);
+    let pdg = PreparedLVDigest::new(&crs, &dg.lv).unwrap();
+    c.bench_function("lv_verify_prepared", |b| b.iter(|| lv_verify_prepared(&pdg, black_box(&pi.lv))));
+}
+
+criterion_group!(benches, bench_prove, bench_verify, bench_verify_prepared);
+criterion_main!(benches);
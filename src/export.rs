@@ -0,0 +1,235 @@
+//src/export.rs
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::PrimeGroup;
+use ark_ff::{Field, One, PrimeField};
+
+use crate::iip::{IIPDigest, IIPProof};
+use crate::scs::CRS;
+use crate::verifier::{ColSide, DigestError, LVDigest, LVProof, ProofElem, build_proof_side_elems, LV_NUM_COORDS};
+
+/// One pairing-product equation Π_k e(g1_k, g2_k) = rhs, in a form an external
+/// verifier (Solidity, Python, ...) can check with a single multi-pairing.
+/// Negative exponents are folded into the G1 operand.
+#[derive(Clone, Debug)]
+pub struct PairingEq<E: Pairing = Bn254> {
+    pub label: String,
+    pub pairs: Vec<(E::G1, E::G2)>,
+    pub rhs: E::TargetField,
+}
+
+impl<E: Pairing> PairingEq<E> {
+    /// Evaluate the equation in-crate.
+    pub fn holds(&self) -> bool {
+        let (a, b): (Vec<E::G1>, Vec<E::G2>) = self.pairs.iter().cloned().unzip();
+        E::multi_pairing(a, b).0 == self.rhs
+    }
+}
+
+/// Why `export_pairing_equations` produced no equations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportError {
+    /// The digest defers its instance; bind one with `with_instance` first.
+    InstanceDeferred,
+    /// A digest component is malformed.
+    Digest(DigestError),
+    /// The proof's IIP and NonZero witness commitments differ.
+    InconsistentProof,
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::InstanceDeferred => write!(f, "digest has no instance bound"),
+            ExportError::Digest(e) => write!(f, "malformed digest: {}", e),
+            ExportError::InconsistentProof => write!(f, "proof witness commitments (IIP vs NonZero) differ"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// The three IIP checks of Construction 6 as pairing products (see `iip_verify`).
+/// `d` must have passed `validate`, so y* is invertible.
+fn iip_equations<E: Pairing>(tag: &str, d: &IIPDigest<E>, pi: &IIPProof<E>) -> Vec<PairingEq<E>> {
+    let g2 = E::G2::generator();
+    let y_inv = d.y_star.inverse().unwrap();
+    let one = E::TargetField::one();
+
+    vec![
+        // C ◦ w = v ◦ [y*^{-1}]_2 + QX ◦ [τ]_2 + QZ ◦ Z
+        PairingEq {
+            label: format!("iip_{}_main", tag),
+            pairs: vec![
+                (d.C, pi.w_tau_2),
+                (-pi.v_g1.mul_bigint(y_inv.into_bigint()), g2),
+                (-pi.QX_tau_1, d.tau_2),
+                (-pi.QZ_tau_1, d.Z_tau_2),
+            ],
+            rhs: one,
+        },
+        // QX ◦ [τ^{N-n+1}]_2 = Q̂X ◦ [1]_2
+        PairingEq {
+            label: format!("iip_{}_qx_degree", tag),
+            pairs: vec![(pi.QX_tau_1, d.tau_N_minus_n_plus_1_2), (-pi.QX_hat_tau_1, g2)],
+            rhs: one,
+        },
+        // v ◦ [τ^N]_2 = v̂ ◦ [1]_2
+        PairingEq {
+            label: format!("iip_{}_v_degree", tag),
+            pairs: vec![(pi.v_g1, d.tau_N_2), (-pi.v_hat_tau_1, g2)],
+            rhs: one,
+        },
+    ]
+}
+
+impl<E: Pairing> LVProof<E> {
+    /// Every pairing check behind `lv_verify`: the gadget checks (IIP x/y,
+    /// plus IIP z and NonZero, which `lv_verify` re-runs in debug builds)
+    /// followed by the rows of A_LV · π = b_LV, each expanded into its column
+    /// pairings. `lv_verify`'s pairing-free prechecks (witness length,
+    /// subgroup membership, degenerate elements) are not included.
+    pub fn export_pairing_equations(&self, crs: &CRS<E>, dg: &LVDigest<E>) -> Result<Vec<PairingEq<E>>, ExportError> {
+        if dg.instance_deferred {
+            return Err(ExportError::InstanceDeferred);
+        }
+        for d in [&dg.iip_x, &dg.iip_y, &dg.iip_z] {
+            d.validate().map_err(ExportError::Digest)?;
+        }
+
+        let g1 = E::G1::generator();
+        let g2 = E::G2::generator();
+
+        let mut eqs = Vec::new();
+        eqs.extend(iip_equations("x", &dg.iip_x, &self.iip_x));
+        eqs.extend(iip_equations("y", &dg.iip_y, &self.iip_y));
+        eqs.extend(iip_equations("z", &dg.iip_z, &self.iip_z));
+
+        // NonZero: e(g1, [B(τ)]_2) = e(g1, g2) · e([Q0(τ)]_1, [τ - d]_2)
+        let d = *crs
+            .domain_points()
+            .get(dg.one_idx)
+            .ok_or(ExportError::Digest(DigestError::OneIdxOutOfRange { one_idx: dg.one_idx, n: crs.n }))?;
+        let tau_minus_d_2 = crs.g2_tau_pow(1) - g2.mul_bigint(d.into_bigint());
+        eqs.push(PairingEq {
+            label: "nonzero".to_string(),
+            pairs: vec![(g1, self.nz.w_tau_2), (-self.nz.q0_tau_1, tau_minus_d_2)],
+            rhs: E::pairing(g1, g2).0,
+        });
+
+        // LV rows: Π_j c_j^{a_ij} = b_i with c_j = e(proof_j, base_j)
+        let shape = dg.linear_shape();
+        let cols = dg.column_metadata(crs).map_err(ExportError::Digest)?;
+        let elems = build_proof_side_elems(crs, dg, self).ok_or(ExportError::InconsistentProof)?;
+        for i in 0..shape.rows {
+            let mut pairs = Vec::new();
+            for j in 0..LV_NUM_COORDS {
+                let e = shape.a[i][j];
+                if e == 0 { continue; }
+                let malformed = || ExportError::Digest(DigestError::MalformedColumn { col: j });
+                let (p, q) = match (cols[j].side, &elems[j]) {
+                    (ColSide::ProofG1PublicG2, ProofElem::G1(p)) => (*p, cols[j].g2_pub.ok_or_else(malformed)?),
+                    (ColSide::ProofG2PublicG1, ProofElem::G2(q)) => (cols[j].g1_pub.ok_or_else(malformed)?, *q),
+                    _ => return Err(malformed()),
+                };
+                pairs.push(if e == 1 { (p, q) } else { (-p, q) });
+            }
+            eqs.push(PairingEq { label: format!("lv_row_{}", i), pairs, rhs: shape.b[i] });
+        }
+
+        Ok(eqs)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod json {
+    use super::PairingEq;
+    use ark_ec::pairing::Pairing;
+    use ark_serialize::CanonicalSerialize;
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    fn hex<T: CanonicalSerialize>(x: &T) -> String {
+        let mut bytes = Vec::new();
+        x.serialize_compressed(&mut bytes).unwrap();
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Points and GT elements are hex strings of their arkworks compressed encoding.
+    impl<E: Pairing> Serialize for PairingEq<E> {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            let pairs: Vec<[String; 2]> =
+                self.pairs.iter().map(|(p, q)| [hex(p), hex(q)]).collect();
+            let mut st = s.serialize_struct("PairingEq", 3)?;
+            st.serialize_field("label", &self.label)?;
+            st.serialize_field("pairs", &pairs)?;
+            st.serialize_field("rhs", &hex(&self.rhs))?;
+            st.end()
+        }
+    }
+
+    pub fn to_json<E: Pairing>(eqs: &[PairingEq<E>]) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(eqs)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use json::to_json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::{bench_setup, mul_prove};
+    use crate::verifier::lv_verify;
+
+    #[test]
+    fn exported_equations_reproduce_lv_verify() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+
+        let eqs = pi.export_pairing_equations(&crs, &dg.lv).unwrap();
+        // 3 IIP checks each for x, y, z, NonZero, and one per LV row
+        assert_eq!(eqs.len(), 9 + 1 + dg.lv.linear_shape().rows);
+        assert!(lv_verify(&crs, &dg.lv, &pi));
+        assert!(eqs.iter().all(|eq| eq.holds()));
+
+        let mut bad = pi.clone();
+        bad.c_tau_1 += crs._g1_tau_pow(0);
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+        let eqs = bad.export_pairing_equations(&crs, &dg.lv).unwrap();
+        assert!(!eqs.iter().all(|eq| eq.holds()));
+
+        let mut bad = pi.clone();
+        bad.iip_x.QZ_tau_1 += crs._g1_tau_pow(0);
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+        let eqs = bad.export_pairing_equations(&crs, &dg.lv).unwrap();
+        assert!(!eqs.iter().all(|eq| eq.holds()));
+
+        assert_eq!(
+            pi.export_pairing_equations(&crs, &dg.lv.defer_instance()).err(),
+            Some(ExportError::InstanceDeferred)
+        );
+
+        let mut wrapped = dg.lv.clone();
+        wrapped.one_idx += crs.n;
+        assert_eq!(
+            pi.export_pairing_equations(&crs, &wrapped).err(),
+            Some(ExportError::Digest(DigestError::OneIdxOutOfRange { one_idx: wrapped.one_idx, n: crs.n }))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_lists_every_equation() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let eqs = pi.export_pairing_equations(&crs, &dg.lv).unwrap();
+
+        let v: serde_json::Value = serde_json::from_str(&to_json(&eqs).unwrap()).unwrap();
+        let arr = v.as_array().unwrap();
+        assert_eq!(arr.len(), eqs.len());
+        for (j, eq) in arr.iter().zip(&eqs) {
+            assert_eq!(j["label"], eq.label.as_str());
+            assert_eq!(j["pairs"].as_array().unwrap().len(), eq.pairs.len());
+        }
+    }
+}
@@ -1,58 +1,212 @@
 // src/helpers.rs
 
-use ark_bn254::Fr;
-use ark_ff::{Zero};
+use ark_ff::{FftField, Field, Zero};
 use std::ops::Mul;
-use ark_poly::{DenseUVPolynomial, univariate::DensePolynomial};
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain, Polynomial, univariate::DensePolynomial};
 
 /// Add a constant to a polynomial: p(X) + c
-pub fn add_constant(p: &DensePolynomial<Fr>, c: Fr) -> DensePolynomial<Fr> {
-    let mut v = p.coeffs().to_vec();
-    if v.is_empty() {
-        v.push(c);
-    } else {
-        v[0] += c;
+/// The zero polynomial (empty coeffs) becomes the constant c (zero again if c = 0).
+pub fn add_constant<F: Field>(p: &DensePolynomial<F>, c: F) -> DensePolynomial<F> {
+    if p.is_zero() {
+        return DensePolynomial::from_coefficients_vec(vec![c]);
     }
+    let mut v = p.coeffs().to_vec();
+    v[0] += c;
     DensePolynomial::from_coefficients_vec(v)
 }
 
 /// Subtract two polynomials: a(X) - b(X)
-pub fn sub_poly(a: &DensePolynomial<Fr>, b: &DensePolynomial<Fr>) -> DensePolynomial<Fr> {
+pub fn sub_poly<F: Field>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> DensePolynomial<F> {
     DensePolynomial::from_coefficients_vec((a - b).coeffs().to_vec())
 }
 
 /// Scale a polynomial by a constant: c * p(X)
-pub fn scale_poly(p: &DensePolynomial<Fr>, c: Fr) -> DensePolynomial<Fr> {
+pub fn scale_poly<F: Field>(p: &DensePolynomial<F>, c: F) -> DensePolynomial<F> {
     DensePolynomial::from_coefficients_vec(
         p.coeffs().iter().map(|x| *x * c).collect()
     )
 }
 
 /// Multiply polynomial by X^k: X^k * p(X)
-pub fn mul_by_xk(p: &DensePolynomial<Fr>, k: usize) -> DensePolynomial<Fr> {
-    let mut v = vec![Fr::zero(); k];
+/// X^k * 0 = 0, so the zero polynomial is returned as-is instead of k padding zeros.
+pub fn mul_by_xk<F: Field>(p: &DensePolynomial<F>, k: usize) -> DensePolynomial<F> {
+    if p.is_zero() {
+        return DensePolynomial::zero();
+    }
+    let mut v = vec![F::zero(); k];
     v.extend_from_slice(p.coeffs());
     DensePolynomial::from_coefficients_vec(v)
 }
 
 /// Multiply two polynomials
-pub fn mul_poly(a: &DensePolynomial<Fr>, b: &DensePolynomial<Fr>) -> DensePolynomial<Fr> {
+pub fn mul_poly<F: FftField>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> DensePolynomial<F> {
         a.mul(b)
     }
 
+/// Multiply two polynomials in evaluation form: FFT both onto a radix-2
+/// domain of the next power of two ≥ deg(a)+deg(b)+1, multiply pointwise,
+/// inverse FFT. O(m log m) instead of the schoolbook O(deg(a)·deg(b)).
+pub fn mul_poly_fft<F: FftField>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> DensePolynomial<F> {
+    if a.is_zero() || b.is_zero() {
+        return DensePolynomial::zero();
+    }
+    let m = (a.degree() + b.degree() + 1).next_power_of_two();
+    let domain = GeneralEvaluationDomain::<F>::new(m).expect("mul_poly_fft: radix-2 domain");
+
+    let mut ea = domain.fft(a.coeffs());
+    let eb = domain.fft(b.coeffs());
+    for (x, y) in ea.iter_mut().zip(eb.iter()) {
+        *x *= y;
+    }
+    domain.ifft_in_place(&mut ea);
+    DensePolynomial::from_coefficients_vec(ea)
+}
+
 /// Create polynomial from coefficient vector
-pub fn poly_from_coeffs(coeffs: Vec<Fr>) -> DensePolynomial<Fr> {
+pub fn poly_from_coeffs<F: Field>(coeffs: Vec<F>) -> DensePolynomial<F> {
         DensePolynomial::from_coefficients_vec(coeffs)
 }
 
 /// Polynomial division with remainder: returns (quotient, remainder)
 /// where dividend = quotient * divisor + remainder
+/// A zero dividend gives (0, 0); a zero divisor is rejected.
 #[allow(non_snake_case)]
-pub fn div_rem(
-        P: &DensePolynomial<Fr>,
-        Q: &DensePolynomial<Fr>,
-    ) -> (DensePolynomial<Fr>, DensePolynomial<Fr>) {
+pub fn div_rem<F: FftField>(
+        P: &DensePolynomial<F>,
+        Q: &DensePolynomial<F>,
+    ) -> (DensePolynomial<F>, DensePolynomial<F>) {
+        assert!(!Q.is_zero(), "div_rem: division by the zero polynomial");
+        if P.is_zero() {
+            return (DensePolynomial::zero(), DensePolynomial::zero());
+        }
         let q = P / Q;
         let r = P - &(&q * Q);
+        // Trim trailing zeros so an exact division always gives r = 0 as empty coeffs
+        let r = DensePolynomial::from_coefficients_vec(r.coeffs().to_vec());
         (q, r)
-    }
\ No newline at end of file
+    }
+
+/// Why `div_rem_exact` produced no quotient.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DivError {
+    /// The divisor is the zero polynomial.
+    ZeroDivisor,
+    /// The remainder is nonzero, of the given degree.
+    NotDivisible { rem_degree: usize },
+}
+
+impl std::fmt::Display for DivError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DivError::ZeroDivisor => write!(f, "division by the zero polynomial"),
+            DivError::NotDivisible { rem_degree } => {
+                write!(f, "not exactly divisible: remainder has degree {}", rem_degree)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DivError {}
+
+/// Quotient P / Q for prover paths that expect exact division; errors
+/// instead of silently dropping a nonzero remainder.
+#[allow(non_snake_case)]
+pub fn div_rem_exact<F: FftField>(
+        P: &DensePolynomial<F>,
+        Q: &DensePolynomial<F>,
+    ) -> Result<DensePolynomial<F>, DivError> {
+        if Q.is_zero() {
+            return Err(DivError::ZeroDivisor);
+        }
+        let (q, r) = div_rem(P, Q);
+        if r.is_zero() {
+            Ok(q)
+        } else {
+            Err(DivError::NotDivisible { rem_degree: r.degree() })
+        }
+    }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    fn poly(c: &[u64]) -> DensePolynomial<Fr> {
+        DensePolynomial::from_coefficients_vec(c.iter().map(|&x| Fr::from(x)).collect())
+    }
+
+    #[test]
+    fn zero_polynomial_edge_cases_are_normalized() {
+        let zero = DensePolynomial::<Fr>::zero();
+        for k in 0..=5 {
+            assert!(mul_by_xk(&zero, k).coeffs().is_empty());
+        }
+
+        let z = poly(&[1, 0, 0, 0, 1]); // X^4 + 1
+        let (q, r) = div_rem(&zero, &z);
+        assert!(q.is_zero() && q.coeffs().is_empty());
+        assert!(r.is_zero() && r.coeffs().is_empty());
+        assert_eq!(div_rem_exact(&zero, &z), Ok(zero.clone()));
+
+        // an exact division leaves an empty remainder, not [0, ..., 0]
+        let (q, r) = div_rem(&mul_poly(&z, &poly(&[2, 3])), &z);
+        assert_eq!(q, poly(&[2, 3]));
+        assert!(r.coeffs().is_empty());
+
+        let c = Fr::from(9u64);
+        assert_eq!(add_constant(&zero, c).coeffs(), &[c]);
+        assert!(add_constant(&zero, Fr::zero()).is_zero());
+        assert_eq!(add_constant(&poly(&[1, 2]), c), poly(&[10, 2]));
+    }
+
+    #[test]
+    fn mul_by_xk_shifts_coefficients() {
+        for c in [&[1u64, 2, 3][..], &[0, 0, 5], &[7]] {
+            let p = poly(c);
+            for k in 0..4 {
+                let mut shifted = vec![Fr::zero(); k];
+                shifted.extend_from_slice(p.coeffs());
+                assert_eq!(mul_by_xk(&p, k).coeffs(), &shifted[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_poly_fft_matches_schoolbook() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed([3; 32]);
+        let mut random = |deg: usize| -> DensePolynomial<Fr> {
+            DensePolynomial::from_coefficients_vec((0..=deg).map(|_| Fr::from(rng.random::<u128>())).collect())
+        };
+
+        for (da, db) in [(0, 0), (1, 0), (3, 5), (100, 37), (255, 256), (512, 512)] {
+            let (a, b) = (random(da), random(db));
+            let mut naive = vec![Fr::zero(); da + db + 1];
+            for (i, x) in a.coeffs().iter().enumerate() {
+                for (j, y) in b.coeffs().iter().enumerate() {
+                    naive[i + j] += *x * y;
+                }
+            }
+            let fft = mul_poly_fft(&a, &b);
+            assert_eq!(fft.coeffs(), &naive[..], "deg {} x deg {}", da, db);
+            assert_eq!(fft, mul_poly(&a, &b));
+        }
+        assert!(mul_poly_fft(&DensePolynomial::zero(), &random(4)).is_zero());
+    }
+
+    #[test]
+    fn div_rem_exact_separates_exact_and_inexact() {
+        let q = poly(&[4, 0, 1]); // X^2 + 4
+        let d = poly(&[3, 1]); // X + 3
+        let p = mul_poly(&q, &d);
+        assert_eq!(div_rem_exact(&p, &d), Ok(q.clone()));
+        let (_, r) = div_rem(&p, &d);
+        assert!(r.coeffs().is_empty());
+
+        // p + X: remainder X mod (X^2 + 4) has degree 1
+        let off = &p + &poly(&[0, 1]);
+        assert_eq!(div_rem_exact(&off, &q), Err(DivError::NotDivisible { rem_degree: 1 }));
+        assert_eq!(div_rem_exact(&(&p + &poly(&[1])), &d), Err(DivError::NotDivisible { rem_degree: 0 }));
+        assert_eq!(div_rem_exact(&p, &DensePolynomial::zero()), Err(DivError::ZeroDivisor));
+    }
+}
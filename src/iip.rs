@@ -1,93 +1,348 @@
 //src/iip.rs
-use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+use ark_bn254::Bn254;
 use ark_ec::PrimeGroup;
 use ark_ec::pairing::Pairing;
 use ark_ff::{Field, One, PrimeField, Zero};
 use ark_poly::{DenseUVPolynomial, Polynomial, univariate::DensePolynomial};
-use crate::helpers::{add_constant, sub_poly, scale_poly, mul_by_xk, mul_poly, poly_from_coeffs, div_rem};
+use crate::helpers::{add_constant, sub_poly, scale_poly, mul_by_xk, mul_poly_fft, poly_from_coeffs, div_rem, div_rem_exact};
 
 use crate::scs::CRS;
+use crate::transcript::Transcript;
+use crate::verifier::{DigestError, gadget_pairing};
 
 /// Public digest (vk) for IIP, as in Construction 6.
 #[allow(non_snake_case)]
 #[allow(dead_code)]
 #[derive(Clone)]
-pub struct IIPDigest {
-    pub x_star: Fr,                           // we use 0
-    pub y_star: Fr,                           // 1/n
-    pub C: G1Projective,                      // y* · [Σ s_i L_i(τ)]_1
-    pub Z_tau_2: G2Projective,                // [Z(τ)]_2
-    pub tau_2: G2Projective,                  // [τ]_2  (since x*=0, [τ - x*]_2 = [τ]_2)
-    pub tau_N_minus_n_plus_2_2: G2Projective, // [τ^{N-n+2}]_2
-    pub tau_N_2: G2Projective,                // [τ^N]_2
+pub struct IIPDigest<E: Pairing = Bn254> {
+    pub x_star: E::ScalarField,         // we use 0
+    pub y_star: E::ScalarField,         // 1/n
+    pub C: E::G1,                       // y* · [Σ s_i L_i(τ)]_1
+    pub Z_tau_2: E::G2,                 // [Z(τ)]_2
+    pub tau_2: E::G2,                   // [τ]_2  (since x*=0, [τ - x*]_2 = [τ]_2)
+    pub tau_N_minus_n_plus_1_2: E::G2,  // [τ^{N-n+1}]_2
+    pub tau_N_2: E::G2,                 // [τ^N]_2
     pub n: usize,
     pub N: usize,
+    pub gadget_tag: &'static str,       // domain separation between IIP instances
+    pub selector_fingerprint: [u8; 32], // selector_fingerprint(s) of the committed selector
 }
 
 #[derive(Clone)]
 #[allow(non_snake_case)]
-pub struct IIPProof {
-    pub w_tau_2: G2Projective,      // [B(τ)]_2 = SCS(G2).Commit(w)
-    pub v_g1: G1Projective,         // v = Σ w_i [s_i]_1
-    pub QZ_tau_1: G1Projective,     // [Q_Z(τ)]_1
-    pub QX_tau_1: G1Projective,     // [Q_X(τ)]_1
-    pub QX_hat_tau_1: G1Projective, // [Q̂_X(τ)]_1 = [X^{N-n+2} Q_X(X)]_1
-    pub v_hat_tau_1: G1Projective,  // [v̂(τ)]_1 = [X^N · (Σ w_i s_i)]_1
+pub struct IIPProof<E: Pairing = Bn254> {
+    pub w_tau_2: E::G2,      // [B(τ)]_2 = SCS(G2).Commit(w)
+    pub v_g1: E::G1,         // v = Σ w_i [s_i]_1
+    pub QZ_tau_1: E::G1,     // [Q_Z(τ)]_1
+    pub QX_tau_1: E::G1,     // [Q_X(τ)]_1
+    pub QX_hat_tau_1: E::G1, // [Q̂_X(τ)]_1 = [X^{N-n+1} Q_X(X)]_1
+    pub v_hat_tau_1: E::G1,  // [v̂(τ)]_1 = [X^N · (Σ w_i s_i)]_1
+}
+
+impl<E: Pairing> IIPDigest<E> {
+    /// Invariants every verifier relies on: y* is invertible, the domain fits
+    /// in the CRS, and the G2 bases are non-trivial. Run this on any digest
+    /// that did not come straight out of `iip_digest`.
+    pub fn validate(&self) -> Result<(), DigestError> {
+        let which = self.gadget_tag;
+        if self.y_star.is_zero() {
+            return Err(DigestError::ZeroYStar { which });
+        }
+        if self.n > self.N {
+            return Err(DigestError::DomainTooLarge { which, n: self.n, max_deg: self.N });
+        }
+        for (field, g) in [
+            ("tau_2", &self.tau_2),
+            ("Z_tau_2", &self.Z_tau_2),
+            ("tau_N_2", &self.tau_N_2),
+        ] {
+            if g.is_zero() {
+                return Err(DigestError::IdentityElement { which, field });
+            }
+        }
+        Ok(())
+    }
+
+    /// The digest was built for selector `s` (compares fingerprints only).
+    pub fn matches_selector(&self, s: &[E::ScalarField]) -> bool {
+        self.selector_fingerprint == selector_fingerprint(s)
+    }
+}
+
+/// k inner products <s, w_k> = v_k under one selector, proven with a single
+/// IIP proof for the Fiat–Shamir combination w* = Σ ρ^k w_k.
+#[derive(Clone)]
+pub struct AggIIPProof<E: Pairing = Bn254> {
+    pub w_tau_2s: Vec<E::G2>,  // [B_k(τ)]_2 per witness
+    pub v_g1s: Vec<E::G1>,     // [v_k]_1 per witness
+    pub combined: IIPProof<E>, // IIP proof for w*
+}
+
+/// IIP digest with the groups swapped: the selector is committed in G2 and
+/// the fixed bases live in G1, so the witness can be committed in G1.
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct IIPDualDigest<E: Pairing = Bn254> {
+    pub y_star: E::ScalarField,        // 1/n
+    pub C: E::G2,                      // [Σ s_i L_i(τ)]_2
+    pub Z_tau_1: E::G1,                // [Z(τ)]_1
+    pub tau_1: E::G1,                  // [τ]_1
+    pub tau_N_minus_n_plus_1_1: E::G1, // [τ^{N-n+1}]_1
+    pub tau_N_1: E::G1,                // [τ^N]_1
+    pub n: usize,
+    pub N: usize,
+    pub gadget_tag: &'static str,
+}
+
+/// Proof for `IIPDualDigest`: `IIPProof` with every element in the other group.
+#[derive(Clone)]
+#[allow(non_snake_case)]
+pub struct IIPDualProof<E: Pairing = Bn254> {
+    pub w_tau_1: E::G1,      // [B(τ)]_1
+    pub v_g2: E::G2,         // [v]_2
+    pub QZ_tau_2: E::G2,     // [Q_Z(τ)]_2
+    pub QX_tau_2: E::G2,     // [Q_X(τ)]_2
+    pub QX_hat_tau_2: E::G2, // [X^{N-n+1} Q_X(X)]_2
+    pub v_hat_tau_2: E::G2,  // [X^N · v]_2
+}
+
+/// Constructors for IIP selectors s in F^n (the public index of `iip_digest`).
+pub struct Selector;
+
+impl Selector {
+    /// e_idx: 1 at `idx`, 0 elsewhere.
+    pub fn one_hot<F: Field>(n: usize, idx: usize) -> Vec<F> {
+        Self::from_indices(n, &[(idx, F::one())])
+    }
+
+    /// Sparse selector: s[i] = c for every (i, c); repeated indices accumulate.
+    pub fn from_indices<F: Field>(n: usize, entries: &[(usize, F)]) -> Vec<F> {
+        let mut s = vec![F::zero(); n];
+        for &(i, c) in entries {
+            assert!(i < n, "Selector: index {} out of range for n={}", i, n);
+            s[i] += c;
+        }
+        s
+    }
+
+    /// e_i - e_j, so that <s, w> = w_i - w_j.
+    pub fn difference<F: Field>(n: usize, i: usize, j: usize) -> Vec<F> {
+        Self::from_indices(n, &[(i, F::one()), (j, -F::one())])
+    }
 }
 
 /// Build vk for IIP given public index s in F^n
+pub fn iip_digest<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField]) -> IIPDigest<E> {
+    iip_digest_tagged(crs, s, "iip")
+}
+
+/// `iip_digest` with the selector length checked, reported as an error
+/// instead of a panic.
+pub fn iip_digest_checked<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField]) -> Result<IIPDigest<E>, IipError> {
+    if crs.n == 0 || s.is_empty() {
+        return Err(IipError::EmptyInput);
+    }
+    if s.len() != crs.n {
+        return Err(IipError::SelectorLength { s: s.len(), n: crs.n });
+    }
+    Ok(iip_digest(crs, s))
+}
+
+/// `iip_digest` with an explicit gadget tag (e.g. "iip_x"), so that headers for
+/// different selectors are domain-separated even when their shapes coincide.
 #[allow(non_snake_case)]
-pub fn iip_digest(crs: &CRS, s: &[Fr]) -> IIPDigest {
+pub fn iip_digest_tagged<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], gadget_tag: &'static str) -> IIPDigest<E> {
+    assert!(crs.n > 0 && !s.is_empty(), "iip_digest: empty selector or n = 0 domain");
     assert_eq!(s.len(), crs.n);
-    // A(X) interpolates s over D
-    let A = crs.interpolate(s);
-    let A_coeffs = A.coeffs();
-
-    
-    let A_tau_1 = crs.commit_poly_g1(A_coeffs);
+    // [A(τ)]_1 with A(X) interpolating s over D, taken straight from the
+    // cached Lagrange basis: Σ s_i [L_i(τ)]_1
+    let A_tau_1 = crs.commit_evals_g1(s);
     //let C = A_tau_1.mul_bigint(crs.n_inv.into_bigint()); // this is the paper’s scaled variant we (Construction 6), we must refactor so that the pairing identity balances well.
     let C = A_tau_1;
-    
 
-    // [Z(τ)]_2 from precomputed coeffs
-    let Z_tau_2 = crs.commit_poly_g2(&crs.vanishing_coeffs);
+    // [Z(τ)]_2 cached on the CRS
+    let Z_tau_2 = crs.Z_tau_2;
 
     IIPDigest {
-        x_star: Fr::zero(),
+        x_star: E::ScalarField::zero(),
         y_star: crs.n_inv,
         C,
         Z_tau_2,
         tau_2: crs.g2_tau_pow(1),
-        tau_N_minus_n_plus_2_2: crs.g2_tau_pow(crs.N - crs.n + 1),
-        //tau_N_minus_n_plus_2_2: crs.g2_tau_pow(crs.N - crs.n + 2),
+        // Construction 6 shifts by N-n+2 because there deg Q_X <= n-2. Here the
+        // R(x*) = 0 adjustment in iip_prove subtracts c·Z (deg n) from R, so
+        // Q_X = R/(X - x*) can reach degree n-1 and the largest shift keeping
+        // X^k Q_X within [τ^0..τ^N] is k = N-(n-1) = N-n+1.
+        tau_N_minus_n_plus_1_2: crs.g2_tau_pow(crs.N - crs.n + 1),
         tau_N_2: crs.g2_tau_pow(crs.N),
         n: crs.n,
         N: crs.N,
+        gadget_tag,
+        selector_fingerprint: selector_fingerprint(s),
+    }
+}
+
+/// SHA-256 of a selector, for checking that a digest was built for an
+/// expected public s without recomputing C from the CRS.
+pub fn selector_fingerprint<F: PrimeField>(s: &[F]) -> [u8; 32] {
+    let mut t = Transcript::new(b"we-snark/selector");
+    t.append_u64(b"n", s.len() as u64);
+    for c in s {
+        t.append_serializable(b"s", c);
     }
+    t.challenge_bytes(b"fingerprint")
+}
+
+/// Why `iip_prove_checked` or `iip_digest_checked` refused to produce a
+/// proof or digest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IipError {
+    /// The CRS domain is empty (n = 0) or s / w has no entries; there is no
+    /// polynomial to interpolate.
+    EmptyInput,
+    /// s or w does not have one entry per domain slot.
+    LengthMismatch { s: usize, w: usize, n: usize },
+    /// A digest's selector s does not have one entry per domain slot.
+    SelectorLength { s: usize, n: usize },
+    /// A(X)B(X) does not sum to v over the domain (s and w are not the
+    /// selector and witness A and B interpolate), or (X - x*) does not
+    /// divide R(X), so Q_X would be wrong.
+    DivisibilityFailed,
+}
+
+impl std::fmt::Display for IipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IipError::LengthMismatch { s, w, n } => {
+                write!(f, "selector len {} / witness len {} != n={}", s, w, n)
+            }
+            IipError::SelectorLength { s, n } => write!(f, "selector len {} != n={}", s, n),
+            IipError::DivisibilityFailed => write!(f, "R(X) not divisible by (X - x*)"),
+            IipError::EmptyInput => write!(f, "empty selector/witness or n = 0 domain"),
+        }
+    }
+}
+
+impl std::error::Error for IipError {}
+
+/// `iip_prove` with its preconditions and the quotient divisibility checked
+/// in every build profile, reported as an error instead of a panic.
+#[allow(non_snake_case)]
+pub fn iip_prove_checked<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], w: &[E::ScalarField]) -> Result<IIPProof<E>, IipError> {
+    if crs.n == 0 || s.is_empty() || w.is_empty() {
+        return Err(IipError::EmptyInput);
+    }
+    if s.len() != crs.n || w.len() != crs.n {
+        return Err(IipError::LengthMismatch { s: s.len(), w: w.len(), n: crs.n });
+    }
+    let A = crs.interpolate(s);
+    let B = crs.interpolate(w);
+    let w_tau_2 = crs.commit_poly_g2(B.coeffs());
+    try_iip_prove_with_polys(crs, s, w, &A, &B, w_tau_2)
 }
 
 /// Prover: compute B(X), v, Q_X, Q_Z, and the “hatted” terms.
 #[allow(non_snake_case)]
-pub fn iip_prove(crs: &CRS, s: &[Fr], w: &[Fr]) -> IIPProof {
-    assert_eq!(s.len(), crs.n);
+pub fn iip_prove<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], w: &[E::ScalarField]) -> IIPProof<E> {
+    assert!(crs.n > 0 && !s.is_empty() && !w.is_empty(), "iip_prove: empty selector/witness or n = 0 domain");
     assert_eq!(w.len(), crs.n);
 
-    // A(X), B(X)
-    let A = crs.interpolate(s);
+    // B(X) and its commitment
     let B = crs.interpolate(w);
-
-    // Commit w
     let w_tau_2 = crs.commit_poly_g2(B.coeffs());
 
+    iip_prove_with_commit(crs, s, w, &B, w_tau_2)
+}
+
+/// Same as `iip_prove`, but reuses a precomputed B(X) = interpolate(w) and
+/// w_tau_2 = [B(τ)]_2. Several selectors over one witness then share a
+/// single G2 commitment; only A, v and the quotients depend on `s`.
+#[allow(non_snake_case)]
+pub fn iip_prove_with_commit<E: Pairing>(
+    crs: &CRS<E>,
+    s: &[E::ScalarField],
+    w: &[E::ScalarField],
+    B: &DensePolynomial<E::ScalarField>,
+    w_tau_2: E::G2,
+) -> IIPProof<E> {
+    assert_eq!(s.len(), crs.n);
+    let A = crs.interpolate(s);
+    iip_prove_with_polys(crs, s, w, &A, B, w_tau_2)
+}
+
+/// Same as `iip_prove_with_commit`, but also takes the selector polynomial
+/// A(X) = interpolate(s), which depends only on the circuit.
+#[allow(non_snake_case)]
+pub fn iip_prove_with_polys<E: Pairing>(
+    crs: &CRS<E>,
+    s: &[E::ScalarField],
+    w: &[E::ScalarField],
+    A: &DensePolynomial<E::ScalarField>,
+    B: &DensePolynomial<E::ScalarField>,
+    w_tau_2: E::G2,
+) -> IIPProof<E> {
+    assert_eq!(s.len(), crs.n);
+    assert_eq!(w.len(), crs.n);
+    try_iip_prove_with_polys(crs, s, w, A, B, w_tau_2).expect("iip_prove: R(X) not divisible by (X - x*)")
+}
+
+#[allow(non_snake_case)]
+fn try_iip_prove_with_polys<E: Pairing>(
+    crs: &CRS<E>,
+    s: &[E::ScalarField],
+    w: &[E::ScalarField],
+    A: &DensePolynomial<E::ScalarField>,
+    B: &DensePolynomial<E::ScalarField>,
+    w_tau_2: E::G2,
+) -> Result<IIPProof<E>, IipError> {
     // v = Σ w_i [s_i]_1
-    let mut v_scalar = Fr::zero();
+    let mut v_scalar = E::ScalarField::zero();
     for (wi, si) in w.iter().zip(s.iter()) {
         v_scalar += *wi * *si;
     }
-    let v_g1 = <Bn254 as Pairing>::G1::generator().mul_bigint(v_scalar.into_bigint());
+    let (QZ, QX) = iip_quotients(crs, A, B, v_scalar)?;
+
+    // Hatted polynomials:
+    // Q̂_X(X) = X^{N-n+1} Q_X(X)  (deg Q_X <= n-1, see iip_digest)
+    let QX_hat = mul_by_xk(&QX, crs.N - crs.n + 1);
+
+    // v = 0: [v]_1 and [v̂(τ)]_1 are both the identity, skip the MSMs
+    let (v_g1, v_hat_tau_1) = if v_scalar.is_zero() {
+        (E::G1::zero(), E::G1::zero())
+    } else {
+        // v̂(X) = X^N * (Σ w_i s_i)  (a pure monomial with that coefficient)
+        let mut vhat_coeffs = vec![E::ScalarField::zero(); crs.N + 1];
+        vhat_coeffs[crs.N] = v_scalar;
+        (
+            E::G1::generator().mul_bigint(v_scalar.into_bigint()),
+            crs.commit_poly_g1(&vhat_coeffs),
+        )
+    };
+
+    Ok(IIPProof {
+        w_tau_2,
+        v_g1,
+        QZ_tau_1: crs.commit_poly_g1(QZ.coeffs()),
+        QX_tau_1: crs.commit_poly_g1(QX.coeffs()),
+        QX_hat_tau_1: crs.commit_poly_g1(QX_hat.coeffs()),
+        v_hat_tau_1,
+    })
+}
+
+/// (Q_Z, Q_X) as returned by `iip_quotients`.
+type Quotients<F> = (DensePolynomial<F>, DensePolynomial<F>);
 
+/// Q_Z and Q_X for A(X)B(X) - v/y* = Q_Z(X)Z(X) + (X - x*)Q_X(X), shared by
+/// both IIP orientations.
+#[allow(non_snake_case)]
+fn iip_quotients<E: Pairing>(
+    crs: &CRS<E>,
+    A: &DensePolynomial<E::ScalarField>,
+    B: &DensePolynomial<E::ScalarField>,
+    v_scalar: E::ScalarField,
+) -> Result<Quotients<E::ScalarField>, IipError> {
     // P(X) = A(X)B(X) - (Σ w_i s_i)/y*
-    let mut P = mul_poly(&A, &B);
+    let mut P = mul_poly_fft(A, B);
     //let t = v_scalar * crs.n_inv.inverse().unwrap();  
     //let t = v_scalar * crs.n_inv;  
     let n_field = crs.n_inv.inverse().unwrap(); 
@@ -108,9 +363,14 @@ pub fn iip_prove(crs: &CRS, s: &[Fr], w: &[Fr]) -> IIPProof {
     let (mut QZ, mut R) = div_rem(&P, &Z);
 
     // 2) Adjust so that R(x*) = 0 with x* = 0:
-    let x_star = Fr::zero();
+    let x_star = E::ScalarField::zero();
     let Z_x = Z.evaluate(&x_star);
     let R_x = R.evaluate(&x_star);
+    // Σ_h A(h)B(h) = n · (AB mod Z)(0), and R = (AB mod Z) - t, so A and B
+    // must sum to v over the domain before R(x*) is shifted onto Q_Z below.
+    if (R_x + t) * n_field != v_scalar {
+        return Err(IipError::DivisibilityFailed);
+    }
     if !R_x.is_zero() {
         // set QZ' = QZ + c with c = R(0)/Z(0), R' = R - c*Z ⇒ R'(0)=0
         let c = R_x * Z_x.inverse().unwrap();
@@ -119,28 +379,13 @@ pub fn iip_prove(crs: &CRS, s: &[Fr], w: &[Fr]) -> IIPProof {
     }
 
     // 3) Now R is divisible by (X - x*), define QX = R / (X - x*)
-    let lin = DensePolynomial::from_coefficients_vec(vec![-x_star, Fr::one()]);
-    let (QX, rem) = div_rem(&R, &lin);
-    debug_assert!(rem.is_zero(), "R(X) not divisible by (X - x*)");
-
-    // Hatted polynomials:
-    // Q̂_X(X) = X^{N-n+1} Q_X(X)
-    let QX_hat = mul_by_xk(&QX, (crs.N - crs.n + 1) as usize);
-    //let QX_hat = mul_by_xk(&QX, (crs.N - crs.n + 2) as usize);
-    
-    // v̂(X) = X^N * (Σ w_i s_i)  (a pure monomial with that coefficient)
-    let mut vhat_coeffs = vec![Fr::zero(); crs.N + 1];
-    vhat_coeffs[crs.N] = v_scalar;
-    let vhat = DensePolynomial::from_coefficients_vec(vhat_coeffs);
-
-    IIPProof {
-        w_tau_2,
-        v_g1,
-        QZ_tau_1: crs.commit_poly_g1(QZ.coeffs()),
-        QX_tau_1: crs.commit_poly_g1(QX.coeffs()),
-        QX_hat_tau_1: crs.commit_poly_g1(QX_hat.coeffs()),
-        v_hat_tau_1: crs.commit_poly_g1(vhat.coeffs()),
+    if !R.evaluate(&x_star).is_zero() {
+        return Err(IipError::DivisibilityFailed);
     }
+    let lin = DensePolynomial::from_coefficients_vec(vec![-x_star, E::ScalarField::one()]);
+    let QX = div_rem_exact(&R, &lin).map_err(|_| IipError::DivisibilityFailed)?;
+
+    Ok((QZ, QX))
 }
 
 /// Verifier: the three linear checks from Construction 6 (no proof–proof pairing).
@@ -150,26 +395,30 @@ pub fn iip_prove(crs: &CRS, s: &[Fr], w: &[Fr]) -> IIPProof {
 // c1 = e(v_g1, y_star^{-1} * g2)
 // c2 = e(QX_tau_1, tau_2)
 // c3 = e(QZ_tau_1, Z_tau_2)
-// c4 = e(QX_tau_1, tau_N_minus_n_plus_2_2)
+// c4 = e(QX_tau_1, tau_N_minus_n_plus_1_2)
 // c5 = e(QX_hat_tau_1, g2)
 // c6 = e(v_g1, tau_N_2)
 // c7 = e(v_hat_tau_1, g2)
 // (NonZero adds c8,c9 in nonzero.rs)
 #[allow(non_snake_case)]
-pub fn iip_verify(d: &IIPDigest, pi: &IIPProof) -> bool {
+pub fn iip_verify<E: Pairing>(d: &IIPDigest<E>, pi: &IIPProof<E>) -> bool {
+    if d.validate().is_err() {
+        return false;
+    }
+
     // 1) C ◦ w = v ◦ [y*^{-1}]_2 + [QX(τ)]_1 ◦ [τ - x*]_2 + [QZ(τ)]_1 ◦ Z
-    let lhs1 = <Bn254 as Pairing>::pairing(d.C, pi.w_tau_2);
+    let lhs1 = gadget_pairing::<E>(d.C, pi.w_tau_2);
 
     // v ◦ [y*^{-1}]_2
     let y_inv = d.y_star.inverse().unwrap();
     let v_g1_scaled = pi.v_g1.mul_bigint(y_inv.into_bigint());
-    let rhs1_v = <Bn254 as Pairing>::pairing(v_g1_scaled, <Bn254 as Pairing>::G2::generator());
+    let rhs1_v = gadget_pairing::<E>(v_g1_scaled, E::G2::generator());
 
     // [QX(τ)]_1 ◦ [τ - x*]_2, and we have x* = 0 ⇒ [τ - x*]_2 = [τ]_2
-    let term_qx = <Bn254 as Pairing>::pairing(pi.QX_tau_1, d.tau_2);
+    let term_qx = gadget_pairing::<E>(pi.QX_tau_1, d.tau_2);
 
     // [QZ(τ)]_1 ◦ Z
-    let term_qz = <Bn254 as Pairing>::pairing(pi.QZ_tau_1, d.Z_tau_2);
+    let term_qz = gadget_pairing::<E>(pi.QZ_tau_1, d.Z_tau_2);
 
     // Multiply underlying GT elements (Fq12) and wrap back into PairingOutput
     let rhs1_total = rhs1_v + term_qx + term_qz;
@@ -178,19 +427,396 @@ pub fn iip_verify(d: &IIPDigest, pi: &IIPProof) -> bool {
         return false;
     }
 
-    // 2) [QX(τ)]_1 ◦ [τ^{N-n+2}]_2 = [Q̂X(τ)]_1 ◦ [1]_2
-    let lhs2 = <Bn254 as Pairing>::pairing(pi.QX_tau_1, d.tau_N_minus_n_plus_2_2);
-    let rhs2 = <Bn254 as Pairing>::pairing(pi.QX_hat_tau_1, <Bn254 as Pairing>::G2::generator());
+    // 2) [QX(τ)]_1 ◦ [τ^{N-n+1}]_2 = [Q̂X(τ)]_1 ◦ [1]_2
+    let lhs2 = gadget_pairing::<E>(pi.QX_tau_1, d.tau_N_minus_n_plus_1_2);
+    let rhs2 = gadget_pairing::<E>(pi.QX_hat_tau_1, E::G2::generator());
     if lhs2 != rhs2 {
         return false;
     }
 
     // 3) v ◦ [τ^N]_2 = [v̂(τ)]_1 ◦ [1]_2
-    let lhs3 = <Bn254 as Pairing>::pairing(pi.v_g1, d.tau_N_2);
-    let rhs3 = <Bn254 as Pairing>::pairing(pi.v_hat_tau_1, <Bn254 as Pairing>::G2::generator());
+    let lhs3 = gadget_pairing::<E>(pi.v_g1, d.tau_N_2);
+    let rhs3 = gadget_pairing::<E>(pi.v_hat_tau_1, E::G2::generator());
     if lhs3 != rhs3 {
         return false;
     }
 
     true
-}
\ No newline at end of file
+}
+
+/// `iip_verify` plus a link to a [v]_1 the caller got elsewhere: the proof
+/// must open to exactly that commitment, so v itself never has to be known.
+pub fn iip_verify_against<E: Pairing>(d: &IIPDigest<E>, pi: &IIPProof<E>, external_v_g1: E::G1) -> bool {
+    pi.v_g1 == external_v_g1 && iip_verify(d, pi)
+}
+
+/// ρ bound to the selector commitment and every per-witness (w_k, v_k).
+fn aggregate_challenge<E: Pairing>(c: &E::G1, w_tau_2s: &[E::G2], v_g1s: &[E::G1]) -> E::ScalarField {
+    let mut t = Transcript::new(b"we-snark/iip-aggregate");
+    t.append_serializable(b"C", c);
+    t.append_u64(b"k", w_tau_2s.len() as u64);
+    for (w, v) in w_tau_2s.iter().zip(v_g1s) {
+        t.append_serializable(b"w", w);
+        t.append_serializable(b"v", v);
+    }
+    t.challenge_scalar(b"rho")
+}
+
+/// Prove <s, w_k> = v_k for every witness at the cost of one IIP proof plus
+/// one G1/G2 commitment per witness.
+#[allow(non_snake_case)]
+pub fn iip_prove_aggregate<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], witnesses: &[Vec<E::ScalarField>]) -> AggIIPProof<E> {
+    assert!(!witnesses.is_empty(), "iip_prove_aggregate: no witnesses");
+    assert_eq!(s.len(), crs.n);
+
+    let g1 = E::G1::generator();
+    let mut w_tau_2s = Vec::with_capacity(witnesses.len());
+    let mut v_g1s = Vec::with_capacity(witnesses.len());
+    for w in witnesses {
+        assert_eq!(w.len(), crs.n);
+        let B = crs.interpolate(w);
+        w_tau_2s.push(crs.commit_poly_g2(B.coeffs()));
+        let v: E::ScalarField = w.iter().zip(s).map(|(wi, si)| *wi * si).sum();
+        v_g1s.push(g1.mul_bigint(v.into_bigint()));
+    }
+
+    // same C as iip_digest, so prover and verifier derive the same ρ
+    let rho = aggregate_challenge::<E>(&crs.commit_evals_g1(s), &w_tau_2s, &v_g1s);
+
+    // w* = Σ ρ^k w_k
+    let mut w_star = vec![E::ScalarField::zero(); crs.n];
+    let mut rho_k = E::ScalarField::one();
+    for w in witnesses {
+        for (acc, wi) in w_star.iter_mut().zip(w) {
+            *acc += rho_k * wi;
+        }
+        rho_k *= rho;
+    }
+
+    AggIIPProof {
+        w_tau_2s,
+        v_g1s,
+        combined: iip_prove(crs, s, &w_star),
+    }
+}
+
+/// Recombine the per-witness commitments with ρ, require the combined proof
+/// to be over exactly those values, then run the ordinary IIP checks.
+pub fn iip_verify_aggregate<E: Pairing>(d: &IIPDigest<E>, pi: &AggIIPProof<E>) -> bool {
+    if pi.w_tau_2s.is_empty() || pi.w_tau_2s.len() != pi.v_g1s.len() {
+        return false;
+    }
+
+    let rho = aggregate_challenge::<E>(&d.C, &pi.w_tau_2s, &pi.v_g1s);
+    let mut w_star = E::G2::zero();
+    let mut v_star = E::G1::zero();
+    let mut rho_k = E::ScalarField::one();
+    for (w, v) in pi.w_tau_2s.iter().zip(&pi.v_g1s) {
+        w_star += w.mul_bigint(rho_k.into_bigint());
+        v_star += v.mul_bigint(rho_k.into_bigint());
+        rho_k *= rho;
+    }
+
+    pi.combined.w_tau_2 == w_star && pi.combined.v_g1 == v_star && iip_verify(d, &pi.combined)
+}
+
+/// `iip_digest_tagged` with the selector committed in G2.
+#[allow(non_snake_case)]
+pub fn iip_digest_g2<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], gadget_tag: &'static str) -> IIPDualDigest<E> {
+    assert_eq!(s.len(), crs.n);
+    let A = crs.interpolate(s);
+
+    IIPDualDigest {
+        y_star: crs.n_inv,
+        C: crs.commit_poly_g2(A.coeffs()),
+        Z_tau_1: crs.Z_tau_1,
+        tau_1: crs._g1_tau_pow(1),
+        tau_N_minus_n_plus_1_1: crs._g1_tau_pow(crs.N - crs.n + 1),
+        tau_N_1: crs._g1_tau_pow(crs.N),
+        n: crs.n,
+        N: crs.N,
+        gadget_tag,
+    }
+}
+
+/// Prover for `IIPDualDigest`: same quotients as `iip_prove`, with B(τ)
+/// committed in G1 and everything else in G2.
+#[allow(non_snake_case)]
+pub fn iip_prove_dual<E: Pairing>(crs: &CRS<E>, s: &[E::ScalarField], w: &[E::ScalarField]) -> IIPDualProof<E> {
+    assert_eq!(s.len(), crs.n);
+    assert_eq!(w.len(), crs.n);
+
+    let A = crs.interpolate(s);
+    let B = crs.interpolate(w);
+    let v_scalar: E::ScalarField = w.iter().zip(s).map(|(wi, si)| *wi * si).sum();
+    let (QZ, QX) = iip_quotients(crs, &A, &B, v_scalar).expect("iip_prove_dual: R(X) not divisible by (X - x*)");
+
+    let QX_hat = mul_by_xk(&QX, crs.N - crs.n + 1);
+    let mut vhat_coeffs = vec![E::ScalarField::zero(); crs.N + 1];
+    vhat_coeffs[crs.N] = v_scalar;
+
+    IIPDualProof {
+        w_tau_1: crs.commit_poly_g1(B.coeffs()),
+        v_g2: E::G2::generator().mul_bigint(v_scalar.into_bigint()),
+        QZ_tau_2: crs.commit_poly_g2(QZ.coeffs()),
+        QX_tau_2: crs.commit_poly_g2(QX.coeffs()),
+        QX_hat_tau_2: crs.commit_poly_g2(QX_hat.coeffs()),
+        v_hat_tau_2: crs.commit_poly_g2(&vhat_coeffs),
+    }
+}
+
+/// The three checks of `iip_verify` with the pairing arguments mirrored.
+pub fn iip_verify_dual<E: Pairing>(d: &IIPDualDigest<E>, pi: &IIPDualProof<E>) -> bool {
+    let Some(y_inv) = d.y_star.inverse() else {
+        return false;
+    };
+    let g1 = E::G1::generator();
+
+    // 1) w ◦ C = [y*^{-1}]_1 ◦ v + [τ]_1 ◦ QX + Z ◦ QZ
+    let lhs1 = E::pairing(pi.w_tau_1, d.C);
+    let rhs1 = E::pairing(g1.mul_bigint(y_inv.into_bigint()), pi.v_g2)
+        + E::pairing(d.tau_1, pi.QX_tau_2)
+        + E::pairing(d.Z_tau_1, pi.QZ_tau_2);
+    if lhs1 != rhs1 {
+        return false;
+    }
+
+    // 2) [τ^{N-n+1}]_1 ◦ QX = [1]_1 ◦ Q̂X
+    if E::pairing(d.tau_N_minus_n_plus_1_1, pi.QX_tau_2)
+        != E::pairing(g1, pi.QX_hat_tau_2)
+    {
+        return false;
+    }
+
+    // 3) [τ^N]_1 ◦ v = [1]_1 ◦ v̂
+    E::pairing(d.tau_N_1, pi.v_g2)
+        == E::pairing(g1, pi.v_hat_tau_2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+
+    fn crs4() -> CRS<Bn254> {
+        CRS::setup(rand::rng(), 4)
+    }
+
+    #[test]
+    fn selectors_have_the_expected_entries() {
+        let f = |v: &[i64]| -> Vec<Fr> { v.iter().map(|&x| Fr::from(x)).collect() };
+        assert_eq!(Selector::one_hot::<Fr>(4, 2), f(&[0, 0, 1, 0]));
+        assert_eq!(Selector::from_indices(4, &[(0, Fr::from(3u64)), (3, Fr::from(5u64)), (0, Fr::from(1u64))]), f(&[4, 0, 0, 5]));
+        assert_eq!(Selector::difference::<Fr>(4, 1, 3), f(&[0, 1, 0, -1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Selector: index 4 out of range for n=4")]
+    fn selector_index_out_of_range_panics() {
+        let _ = Selector::one_hot::<Fr>(4, 4);
+    }
+
+    #[test]
+    fn lagrange_digest_matches_interpolation() {
+        use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+        let coset = GeneralEvaluationDomain::<Fr>::new_coset(4, Fr::from(5u64)).unwrap();
+        for crs in [crs4(), CRS::<Bn254>::setup_with_domain(rand::rng(), coset)] {
+            for i in 0..4 {
+                let e: Vec<Fr> = Selector::one_hot(4, i);
+                assert_eq!(crs.lagrange_table()[i], crs.commit_poly_g1(crs.interpolate(&e).coeffs()));
+            }
+            let selectors: [Vec<Fr>; 3] = [
+                Selector::one_hot(4, 2),
+                Selector::difference(4, 0, 3),
+                (1..=4u64).map(Fr::from).collect(),
+            ];
+            for s in &selectors {
+                let by_interpolation = crs.commit_poly_g1(crs.interpolate(s).coeffs());
+                assert_eq!(iip_digest(&crs, s).C, by_interpolation);
+            }
+        }
+    }
+
+    #[test]
+    fn dual_orientation_verifies_and_opens_v() {
+        let crs = crs4();
+        let w: Vec<Fr> = [12u64, 17, 204, 1].into_iter().map(Fr::from).collect();
+        let s_x: Vec<Fr> = Selector::one_hot(4, 0);
+        let d = iip_digest_g2(&crs, &s_x, "iip_x");
+        let g2 = <Bn254 as Pairing>::G2::generator();
+
+        let pi = iip_prove_dual(&crs, &s_x, &w);
+        assert!(iip_verify_dual(&d, &pi));
+        assert_eq!(pi.v_g2, g2.mul_bigint(Fr::from(12u64).into_bigint()));
+        assert_eq!(pi.w_tau_1, crs.commit_poly_g1(crs.interpolate(&w).coeffs()));
+
+        let mut bad = pi.clone();
+        bad.v_g2 += g2;
+        assert!(!iip_verify_dual(&d, &bad));
+        // a proof for another selector does not verify against s_x
+        assert!(!iip_verify_dual(&d, &iip_prove_dual(&crs, &Selector::one_hot(4, 1), &w)));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn inconsistent_pairing_is_an_error_not_a_debug_assert() {
+        let crs = crs4();
+        let s = Selector::one_hot::<Fr>(4, 1);
+        let w: Vec<Fr> = [3u64, 5, 7, 11].into_iter().map(Fr::from).collect();
+        assert!(iip_verify(&iip_digest(&crs, &s), &iip_prove_checked(&crs, &s, &w).unwrap()));
+
+        // A interpolates a different selector than the s that v is taken from;
+        // the check is a plain branch, so it holds with debug assertions off too
+        let A = crs.interpolate(&Selector::one_hot::<Fr>(4, 2));
+        let B = crs.interpolate(&w);
+        let w_tau_2 = crs.commit_poly_g2(B.coeffs());
+        assert_eq!(
+            try_iip_prove_with_polys(&crs, &s, &w, &A, &B, w_tau_2).err(),
+            Some(IipError::DivisibilityFailed)
+        );
+        assert_eq!(
+            iip_prove_checked(&crs, &s, &w[..3]).err(),
+            Some(IipError::LengthMismatch { s: 4, w: 3, n: 4 })
+        );
+    }
+
+    #[test]
+    fn selector_fingerprints_identify_the_selector() {
+        let (a, b) = (crs4(), crs4());
+        let s = Selector::one_hot::<Fr>(4, 1);
+        let t = Selector::one_hot::<Fr>(4, 2);
+
+        // same selector: same fingerprint, even under unrelated CRSs
+        let (da, db) = (iip_digest(&a, &s), iip_digest(&b, &s));
+        assert_ne!(da.C, db.C);
+        assert_eq!(da.selector_fingerprint, db.selector_fingerprint);
+        assert!(da.matches_selector(&s));
+
+        let dt = iip_digest(&a, &t);
+        assert_ne!(da.selector_fingerprint, dt.selector_fingerprint);
+        assert!(!dt.matches_selector(&s));
+    }
+
+    #[test]
+    fn verify_against_links_v_to_an_external_commitment() {
+        let crs = crs4();
+        let s = Selector::from_indices(4, &[(0, Fr::from(2u64)), (2, Fr::from(3u64))]);
+        let w: Vec<Fr> = [5u64, 7, 11, 13].into_iter().map(Fr::from).collect();
+        let d = iip_digest(&crs, &s);
+        let pi = iip_prove(&crs, &s, &w);
+
+        // v = 2·5 + 3·11 = 43, committed elsewhere as [43]_1
+        let g1 = <Bn254 as Pairing>::G1::generator();
+        assert!(iip_verify_against(&d, &pi, g1 * Fr::from(43u64)));
+        assert!(!iip_verify_against(&d, &pi, g1 * Fr::from(44u64)));
+    }
+
+    #[test]
+    fn zero_inner_product_still_verifies() {
+        let crs = crs4();
+        // 2·3 + 1·(-6) = 0 with every slot nonzero
+        let s = Selector::from_indices(4, &[(0, Fr::from(2u64)), (1, Fr::from(1u64))]);
+        let w = vec![Fr::from(3u64), -Fr::from(6u64), Fr::from(5u64), Fr::from(1u64)];
+        let d = iip_digest(&crs, &s);
+        let pi = iip_prove(&crs, &s, &w);
+        assert!(pi.v_g1.is_zero() && pi.v_hat_tau_1.is_zero());
+        assert!(iip_verify(&d, &pi));
+
+        // a selector that misses the witness entirely
+        let s0 = Selector::one_hot::<Fr>(4, 2);
+        let w0 = vec![Fr::from(3u64), Fr::from(4u64), Fr::from(0u64), Fr::from(1u64)];
+        let pi0 = iip_prove(&crs, &s0, &w0);
+        assert!(pi0.v_g1.is_zero());
+        assert!(iip_verify(&iip_digest(&crs, &s0), &pi0));
+    }
+
+    fn proof_bytes(pi: &IIPProof<Bn254>) -> Vec<u8> {
+        use ark_serialize::CanonicalSerialize;
+        let mut out = Vec::new();
+        pi.w_tau_2.serialize_compressed(&mut out).unwrap();
+        for g in [&pi.v_g1, &pi.QZ_tau_1, &pi.QX_tau_1, &pi.QX_hat_tau_1, &pi.v_hat_tau_1] {
+            g.serialize_compressed(&mut out).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn shared_commit_gives_identical_proofs() {
+        let crs = crs4();
+        let w: Vec<Fr> = [1u64, 12, 17, 204].into_iter().map(Fr::from).collect();
+        let B = crs.interpolate(&w);
+        let w_tau_2 = crs.commit_poly_g2(B.coeffs());
+
+        for idx in 1..4 {
+            let s: Vec<Fr> = Selector::one_hot(4, idx);
+            let shared = iip_prove_with_commit(&crs, &s, &w, &B, w_tau_2);
+            assert_eq!(proof_bytes(&shared), proof_bytes(&iip_prove(&crs, &s, &w)));
+            assert!(iip_verify(&iip_digest(&crs, &s), &shared));
+        }
+    }
+
+    #[test]
+    fn digest_checked_reports_selector_length() {
+        let crs = crs4();
+        let s: Vec<Fr> = Selector::one_hot(3, 0);
+        assert_eq!(iip_digest_checked(&crs, &s).err(), Some(IipError::SelectorLength { s: 3, n: 4 }));
+        assert_eq!(iip_digest_checked::<Bn254>(&crs, &[]).err(), Some(IipError::EmptyInput));
+        assert!(iip_digest_checked(&crs, &Selector::one_hot::<Fr>(4, 0)).is_ok());
+    }
+
+    #[test]
+    fn aggregate_rejects_one_wrong_witness_among_eight() {
+        let crs = crs4();
+        let s: Vec<Fr> = (1..=4u64).map(Fr::from).collect();
+        let d = iip_digest(&crs, &s);
+        let witnesses: Vec<Vec<Fr>> =
+            (0..8u64).map(|k| (0..4u64).map(|i| Fr::from(10 * k + i)).collect()).collect();
+
+        let pi = iip_prove_aggregate(&crs, &s, &witnesses);
+        assert_eq!(pi.w_tau_2s.len(), 8);
+        assert!(iip_verify_aggregate(&d, &pi));
+
+        // witness 5 claims <s, w_5> + 1
+        let mut bad = pi.clone();
+        bad.v_g1s[5] += <Bn254 as Pairing>::G1::generator();
+        assert!(!iip_verify_aggregate(&d, &bad));
+
+        // witness 5 swapped for another vector under the same claimed value
+        let mut other = witnesses.clone();
+        other[5] = vec![Fr::from(1u64), Fr::from(2u64), Fr::from(3u64), Fr::from(4u64)];
+        let mut bad = pi.clone();
+        bad.w_tau_2s[5] = iip_prove_aggregate(&crs, &s, &other).w_tau_2s[5];
+        assert!(!iip_verify_aggregate(&d, &bad));
+
+        // dropping a witness breaks the binding to the combined proof
+        let mut short = pi.clone();
+        short.w_tau_2s.pop();
+        short.v_g1s.pop();
+        assert!(!iip_verify_aggregate(&d, &short));
+    }
+
+    /// The QX_hat shift, from first principles: the CRS commits to degree at
+    /// most N ([τ^0..τ^N]), so X^k·Q_X needs k + deg Q_X <= N. With v ≠ 0 the
+    /// R(x*) = 0 adjustment subtracts a multiple of Z from R, Q_X reaches
+    /// degree n-1 and k = N-n+1 is the largest shift that fits.
+    #[test]
+    #[allow(non_snake_case)]
+    fn qx_hat_shift_is_the_largest_that_fits() {
+        let crs = crs4();
+        let s = Selector::one_hot::<Fr>(crs.n, 0);
+        let w: Vec<Fr> = [5u64, 6, 7, 1].into_iter().map(Fr::from).collect();
+        let v = s.iter().zip(&w).map(|(a, b)| *a * b).sum::<Fr>();
+        let (_, QX) = iip_quotients(&crs, &crs.interpolate(&s), &crs.interpolate(&w), v).unwrap();
+        assert_eq!(QX.degree(), crs.n - 1);
+
+        // one power of τ per coefficient slot 0..=N
+        assert_eq!(crs.g1_pows.len(), crs.N + 1);
+        assert_eq!(mul_by_xk(&QX, crs.N - crs.n + 1).degree(), crs.N);
+        assert_eq!(mul_by_xk(&QX, crs.N - crs.n + 2).degree(), crs.g1_pows.len());
+
+        let d = iip_digest(&crs, &s);
+        assert_eq!(d.tau_N_minus_n_plus_1_2, crs.g2_tau_pow(crs.N - crs.n + 1));
+        assert!(iip_verify(&d, &iip_prove(&crs, &s, &w)));
+    }
+}
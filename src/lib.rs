@@ -0,0 +1,13 @@
+pub mod scs;
+pub mod iip;
+pub mod nonzero;
+pub mod verifier;
+pub mod we;
+pub mod mul_snark;
+pub mod helpers;
+pub mod transcript;
+pub mod export;
+pub mod metrics;
+pub mod prelude;
+pub mod trivial;
+pub mod maxdeg;
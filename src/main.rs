@@ -1,20 +1,10 @@
-mod scs;
-mod iip;
-mod nonzero;
-mod verifier;
-mod we;
-mod mul_snark;
-mod helpers;
-
 use ark_bn254::Fr;
 use ark_serialize::CanonicalSerialize;
-use rand::{rng, Rng};
+use rand::rng;
 use std::time::Instant;
 
-use scs::CRS;
-use we::{aead_encrypt, decrypt_with_lv_header};
-use mul_snark::{MulDigest, MulWitness, mul_prove};
-use crate::verifier::{lv_verify};
+use we_snark::prelude::*;
+use we_snark::we::{self, HeaderSealer, NonceStrategy};
 
 
 fn serialized_size<T: CanonicalSerialize>(t: &T) -> usize {
@@ -50,13 +40,13 @@ fn main() {
     
     // Digest size: manually calculate from components
     let digest_size = serialized_size(&dg.lv.iip_x.C) + serialized_size(&dg.lv.iip_x.Z_tau_2) + 
-                      serialized_size(&dg.lv.iip_x.tau_2) + serialized_size(&dg.lv.iip_x.tau_N_minus_n_plus_2_2) + 
+                      serialized_size(&dg.lv.iip_x.tau_2) + serialized_size(&dg.lv.iip_x.tau_N_minus_n_plus_1_2) + 
                       serialized_size(&dg.lv.iip_x.tau_N_2) +
                       serialized_size(&dg.lv.iip_y.C) + serialized_size(&dg.lv.iip_y.Z_tau_2) + 
-                      serialized_size(&dg.lv.iip_y.tau_2) + serialized_size(&dg.lv.iip_y.tau_N_minus_n_plus_2_2) + 
+                      serialized_size(&dg.lv.iip_y.tau_2) + serialized_size(&dg.lv.iip_y.tau_N_minus_n_plus_1_2) + 
                       serialized_size(&dg.lv.iip_y.tau_N_2) +
                       serialized_size(&dg.lv.iip_z.C) + serialized_size(&dg.lv.iip_z.Z_tau_2) + 
-                      serialized_size(&dg.lv.iip_z.tau_2) + serialized_size(&dg.lv.iip_z.tau_N_minus_n_plus_2_2) + 
+                      serialized_size(&dg.lv.iip_z.tau_2) + serialized_size(&dg.lv.iip_z.tau_N_minus_n_plus_1_2) + 
                       serialized_size(&dg.lv.iip_z.tau_N_2) +
                       serialized_size(&dg.lv.mul_z_tau_2) + serialized_size(&dg.lv.instance_z) + 
                       serialized_size(&dg.lv.tau_N_minus_d_1);
@@ -66,25 +56,14 @@ fn main() {
     let witness_size = serialized_size(&w.x) + serialized_size(&w.y) + serialized_size(&w.z);
     println!("Witness (x, y, z): {}", witness_size);
     
-    // Proof size: manually calculate from components
-    let proof_size = serialized_size(&pi.lv.iip_x.w_tau_2) + serialized_size(&pi.lv.iip_x.v_g1) +
-                     serialized_size(&pi.lv.iip_x.QZ_tau_1) + serialized_size(&pi.lv.iip_x.QX_tau_1) +
-                     serialized_size(&pi.lv.iip_x.QX_hat_tau_1) + serialized_size(&pi.lv.iip_x.v_hat_tau_1) +
-                     serialized_size(&pi.lv.iip_y.w_tau_2) + serialized_size(&pi.lv.iip_y.v_g1) +
-                     serialized_size(&pi.lv.iip_y.QZ_tau_1) + serialized_size(&pi.lv.iip_y.QX_tau_1) +
-                     serialized_size(&pi.lv.iip_y.QX_hat_tau_1) + serialized_size(&pi.lv.iip_y.v_hat_tau_1) +
-                     serialized_size(&pi.lv.iip_z.w_tau_2) + serialized_size(&pi.lv.iip_z.v_g1) +
-                     serialized_size(&pi.lv.iip_z.QZ_tau_1) + serialized_size(&pi.lv.iip_z.QX_tau_1) +
-                     serialized_size(&pi.lv.iip_z.QX_hat_tau_1) + serialized_size(&pi.lv.iip_z.v_hat_tau_1) +
-                     serialized_size(&pi.lv.nz.q0_tau_1) + serialized_size(&pi.lv.nz.w_tau_2) +
-                     serialized_size(&pi.lv.p_tau_1) + serialized_size(&pi.lv.h_tau_1) +
-                     serialized_size(&pi.lv.a_tau_1) + serialized_size(&pi.lv.b_tau_1) +
-                     serialized_size(&pi.lv.c_tau_1) +
-                     serialized_size(&pi.lv.w_hat_tau_1);
-    println!("LV Proof: {}", proof_size);
+    // Proof size, per gadget (compressed encoding)
+    for (gadget, bytes) in pi.lv.size_breakdown() {
+        println!("  {}: {}", gadget, bytes);
+    }
+    println!("LV Proof: {}", pi.lv.to_bytes().len());
 
     // --- Encryptor's public LV params and header (no witness needed) ---
-    let params = we::lv_public_linear_params(&crs, &dg.lv);
+    let params = we::lv_public_linear_params(&crs, &dg.lv).expect("digest column metadata");
     let (hdr, key_enc) = we::lv_make_header(&params, &crs, &mut rng);
     
     // Header size: manually calculate
@@ -99,10 +78,10 @@ fn main() {
 
     // --- AEAD encrypt ---
     let mut msg = b"hello secret world".to_vec();
-    let nonce: [u8; 12] = rng.random();
+    let mut sealer = HeaderSealer::new(key_enc, NonceStrategy::Random);
     
     let enc_start = Instant::now();
-    let tag: Vec<u8> = aead_encrypt(&crs, &params.shape, &hdr, key_enc, nonce, &mut msg);
+    let (nonce, tag) = sealer.seal(&crs, &params, &hdr, &mut rng, &mut msg);
     let enc_time = enc_start.elapsed();
     
     let ciphertext_size = msg.len();
@@ -123,7 +102,7 @@ fn main() {
     println!("Decryption: {:?}", dec_time);
     
     match maybe_pt {
-        Some(pt) => println!("\n=== RESULT ===\nDecryption OK: {}", String::from_utf8_lossy(&pt)),
-        None => println!("\n=== RESULT ===\nDecryption failed"),
+        Ok(pt) => println!("\n=== RESULT ===\nDecryption OK: {}", String::from_utf8_lossy(&pt)),
+        Err(e) => println!("\n=== RESULT ===\nDecryption failed: {}", e),
     }
 }
\ No newline at end of file
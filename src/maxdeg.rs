@@ -0,0 +1,54 @@
+//src/maxdeg.rs
+use ark_ec::pairing::Pairing;
+use ark_ec::PrimeGroup;
+use ark_ff::Zero;
+
+use crate::scs::CRS;
+
+/// MaxDeg: deg F <= d is shown by [τ^{N-d} F(τ)]_1, the commitment to
+/// X^{N-d}·F(X), which only exists within the CRS powers when deg F <= d.
+/// Returns None when deg F > d or d > N, since no such commitment can be
+/// formed.
+pub fn maxdeg_prove<E: Pairing>(crs: &CRS<E>, coeffs: &[E::ScalarField], d_bound: usize) -> Option<E::G1> {
+    let deg = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
+    if d_bound > crs.N || deg > d_bound {
+        return None;
+    }
+    Some(crs.commit_shifted(coeffs, crs.N - d_bound))
+}
+
+/// e([τ^{N-d}]_1, [F(τ)]_2) = e(ŵ, [1]_2) for the G2 commitment to F and
+/// ŵ = `maxdeg_prove(crs, F, d)`; the c16·c17^{-1} = 1 row of the LV system.
+pub fn maxdeg_verify<E: Pairing>(crs: &CRS<E>, commitment_g2: E::G2, w_hat_tau_1: E::G1, d_bound: usize) -> bool {
+    if d_bound > crs.N {
+        return false;
+    }
+    E::pairing(crs._g1_tau_pow(crs.N - d_bound), commitment_g2) == E::pairing(w_hat_tau_1, E::G2::generator())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn degree_two_passes_and_degree_four_fails_a_bound_of_three() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let f = |c: &[u64]| -> Vec<Fr> { c.iter().map(|&x| Fr::from(x)).collect() };
+
+        let quad = f(&[1, 2, 3]);
+        let w_hat = maxdeg_prove(&crs, &quad, 3).unwrap();
+        assert!(maxdeg_verify(&crs, crs.commit_poly_g2(&quad), w_hat, 3));
+
+        let quartic = f(&[1, 0, 0, 0, 5]);
+        let com = crs.commit_poly_g2(&quartic);
+        assert!(maxdeg_prove(&crs, &quartic, 3).is_none());
+        // the degree-4 witness is valid for d = 4 only
+        let w4 = maxdeg_prove(&crs, &quartic, 4).unwrap();
+        assert!(maxdeg_verify(&crs, com, w4, 4));
+        assert!(!maxdeg_verify(&crs, com, w4, 3));
+
+        assert!(maxdeg_prove(&crs, &quad, crs.N + 1).is_none());
+        assert!(!maxdeg_verify(&crs, crs.commit_poly_g2(&quad), w_hat, crs.N + 1));
+    }
+}
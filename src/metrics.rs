@@ -0,0 +1,57 @@
+//src/metrics.rs
+use std::time::Duration;
+
+/// Timing hooks for the prover and verifier, passed to
+/// `mul_prove_with_metrics` / `lv_verify_with_metrics`. Every callback
+/// defaults to a no-op, so an implementation only overrides what it records.
+pub trait Metrics {
+    fn on_prove_start(&mut self) {}
+    fn on_prove_end(&mut self, _dur: Duration) {}
+    /// One verifier stage finished: "precheck", "shape", "coords",
+    /// "linear_check", in that order. A rejected proof stops at the stage
+    /// that rejected it.
+    fn on_verify_stage(&mut self, _name: &'static str, _dur: Duration) {}
+}
+
+/// Records nothing; what the plain `mul_prove` / `lv_verify` run with.
+pub struct NoMetrics;
+
+impl Metrics for NoMetrics {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::{bench_setup, mul_prove_with_metrics};
+    use crate::verifier::lv_verify_with_metrics;
+
+    #[derive(Default)]
+    struct Recorder(Vec<&'static str>);
+
+    impl Metrics for Recorder {
+        fn on_prove_start(&mut self) {
+            self.0.push("prove_start");
+        }
+        fn on_prove_end(&mut self, _dur: Duration) {
+            self.0.push("prove_end");
+        }
+        fn on_verify_stage(&mut self, name: &'static str, _dur: Duration) {
+            self.0.push(name);
+        }
+    }
+
+    #[test]
+    fn stages_fire_in_order() {
+        let (crs, dg, w) = bench_setup();
+        let mut rec = Recorder::default();
+        let pi = mul_prove_with_metrics(&crs, &dg, &w, &mut rec).lv;
+        assert!(lv_verify_with_metrics(&crs, &dg.lv, &pi, &mut rec));
+        assert_eq!(rec.0, ["prove_start", "prove_end", "precheck", "shape", "coords", "linear_check"]);
+
+        // a proof rejected by the prechecks stops there
+        let mut bad = pi.clone();
+        bad.w.push(w.x);
+        let mut rec = Recorder::default();
+        assert!(!lv_verify_with_metrics(&crs, &dg.lv, &bad, &mut rec));
+        assert_eq!(rec.0, ["precheck"]);
+    }
+}
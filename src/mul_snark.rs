@@ -1,58 +1,87 @@
 // src/mul_snark.rs
 
-use ark_bn254::{Bn254, Fr, G1Projective as G1};
+use ark_bn254::{Bn254, Fr};
 use ark_ec::PrimeGroup;
 use ark_ec::pairing::Pairing;
-use ark_ff::{One, Zero};
+use ark_ff::{FftField, Field, One, Zero};
 use ark_poly::{DenseUVPolynomial, Polynomial, univariate::DensePolynomial};
 
-use crate::iip::{iip_digest, iip_prove};
+use crate::iip::{Selector, iip_digest_tagged, iip_prove_with_polys};
 use crate::nonzero::nonzero_prove;
+use crate::maxdeg::maxdeg_prove;
+use crate::metrics::Metrics;
 use crate::scs::CRS;
 use crate::verifier::{LVDigest, LVProof};
-use crate::helpers::{mul_poly, div_rem, mul_by_xk};
+use crate::helpers::{mul_poly, div_rem};
+use std::time::Instant;
 
 /// Fixed-size MulCircuit witness: w = [x, y, z, 1].
 #[derive(Clone, Debug)]
-pub struct MulWitness {
-    pub x: Fr,
-    pub y: Fr,
-    pub z: Fr,
+pub struct MulWitness<F: Field = Fr> {
+    pub x: F,
+    pub y: F,
+    pub z: F,
 }
 
-impl MulWitness {
+impl<F: Field> MulWitness<F> {
     /// Convert to the evaluation vector [x, y, z, 1] on D.
-    pub fn to_vec(&self) -> Vec<Fr> {
-        vec![self.x, self.y, self.z, Fr::from(1u32)]
+    pub fn to_vec(&self) -> Vec<F> {
+        vec![self.x, self.y, self.z, F::one()]
     }
 }
 
 /// Public parameters (vk) for the LV-SNARK.
 /// For now this is just a wrapper around LVDigest + the public index s.
 #[derive(Clone)]
-pub struct MulDigest {
-    pub lv: LVDigest,
+pub struct MulDigest<E: Pairing = Bn254> {
+    pub lv: LVDigest<E>,
     // Selectors for the three witness slots
-    pub s_x: Vec<Fr>, // [1,0,0,0]
-    pub s_y: Vec<Fr>, // [0,1,0,0]
-    pub s_z: Vec<Fr>, // [0,0,1,0]
+    pub s_x: Vec<E::ScalarField>, // [1,0,0,0]
+    pub s_y: Vec<E::ScalarField>, // [0,1,0,0]
+    pub s_z: Vec<E::ScalarField>, // [0,0,1,0]
 }
 
 /// Proof object for MulCircuit: reuses LVProof as-is.
 #[derive(Clone)]
-pub struct MulProof {
-    pub lv: LVProof,
+pub struct MulProof<E: Pairing = Bn254> {
+    pub lv: LVProof<E>,
 }
 
 /// QAP polynomials for the one-gate MulCircuit:
 /// A(X) = x, B(X) = y, C(X) = z, Z(X) = X - 1, P(X) = A(X)B(X) - C(X).
 #[derive(Clone)]
-pub struct MulQAPPolys {
-    pub a: DensePolynomial<Fr>,
-    pub b: DensePolynomial<Fr>,
-    pub c: DensePolynomial<Fr>,
-    pub p: DensePolynomial<Fr>,
-    pub z: DensePolynomial<Fr>,
+pub struct MulQAPPolys<F: Field = Fr> {
+    pub a: DensePolynomial<F>,
+    pub b: DensePolynomial<F>,
+    pub c: DensePolynomial<F>,
+    pub p: DensePolynomial<F>,
+    pub z: DensePolynomial<F>,
+}
+
+/// Circuit-fixed prover data for MulCircuit: the selector polynomials
+/// A_x, A_y, A_z and the QAP vanishing polynomial Z(X) = X - 1. Build it
+/// once per digest and reuse it across `mul_prove_with_pk` calls.
+#[derive(Clone)]
+pub struct MulProvingKey<F: Field = Fr> {
+    pub a_x: DensePolynomial<F>,
+    pub a_y: DensePolynomial<F>,
+    pub a_z: DensePolynomial<F>,
+    pub mul_z: DensePolynomial<F>,
+}
+
+impl<F: Field> MulProvingKey<F> {
+    pub fn setup<E: Pairing<ScalarField = F>>(crs: &CRS<E>, dg: &MulDigest<E>) -> Self {
+        let [a_x, a_y, a_z]: [DensePolynomial<F>; 3] = crs
+            .interpolate_batch(&[dg.s_x.clone(), dg.s_y.clone(), dg.s_z.clone()])
+            .try_into()
+            .expect("interpolate_batch returns one polynomial per selector");
+        MulProvingKey {
+            a_x,
+            a_y,
+            a_z,
+            mul_z: DensePolynomial::from_coefficients_vec(vec![-F::one(), F::one()]),
+        }
+    }
 }
 
 /// KZG commitments to the QAP polynomials.
@@ -60,16 +89,17 @@ pub struct MulQAPPolys {
 /// debug mode to sanity-check the polynomial identities and prepare for
 /// a future Mul gadget.
 #[derive(Clone)]
-pub struct MulQAPCommit {
-    pub a_tau_1: G1,
-    pub b_tau_1: G1,
-    pub c_tau_1: G1,
-    pub p_tau_1: G1,
-    pub h_tau_1: G1,
+pub struct MulQAPCommit<E: Pairing = Bn254> {
+    pub a_tau_1: E::G1,
+    pub b_tau_1: E::G1,
+    pub c_tau_1: E::G1,
+    pub p_tau_1: E::G1,
+    pub h_tau_1: E::G1,
 }
 
-/// Build QAP polynomials from the Mul witness w = [x,y,z,1].
-fn build_mul_qap_polys(w: &MulWitness) -> MulQAPPolys {
+/// Build QAP polynomials from the Mul witness w = [x,y,z,1]; Z(X) comes
+/// from the proving key.
+fn build_mul_qap_polys<F: FftField>(w: &MulWitness<F>, mul_z: &DensePolynomial<F>) -> MulQAPPolys<F> {
     let x = w.x;
     let y = w.y;
     let z = w.z;
@@ -96,20 +126,17 @@ fn build_mul_qap_polys(w: &MulWitness) -> MulQAPPolys {
         p = DensePolynomial::from_coefficients_vec(p_coeffs);
     }
 
-    // Z(X) = X - 1
-    let z_poly = DensePolynomial::from_coefficients_vec(vec![-Fr::one(), Fr::one()]);
-
     MulQAPPolys {
         a,
         b,
         c,
         p,
-        z: z_poly,
+        z: mul_z.clone(),
     }
 }
 
 /// Commit the QAP polynomials with the SCS (KZG).
-fn commit_mul_qap(crs: &CRS, polys: &MulQAPPolys) -> MulQAPCommit {
+fn commit_mul_qap<E: Pairing>(crs: &CRS<E>, polys: &MulQAPPolys<E::ScalarField>) -> MulQAPCommit<E> {
     let a_tau_1 = crs.commit_poly_g1(polys.a.coeffs());
     let b_tau_1 = crs.commit_poly_g1(polys.b.coeffs());
     let c_tau_1 = crs.commit_poly_g1(polys.c.coeffs());
@@ -127,10 +154,12 @@ fn commit_mul_qap(crs: &CRS, polys: &MulQAPPolys) -> MulQAPCommit {
     }
 }
 
-fn compute_h_poly(_crs: &CRS, polys: &MulQAPPolys) -> DensePolynomial<Fr> {
+fn compute_h_poly<E: Pairing>(_crs: &CRS<E>, polys: &MulQAPPolys<E::ScalarField>) -> DensePolynomial<E::ScalarField> {
     // H(X) = P(X) / Z(X), with Z(X) = X - 1
     let (h, r) = div_rem(&polys.p, &polys.z);
-    debug_assert!(
+    // always on: the remainder is already computed, and a bad witness must
+    // not yield a proof in release builds either
+    assert!(
         r.coeffs().iter().all(|c| c.is_zero()),
         "Mul QAP: P(X) is not divisible by Z(X); bad witness"
     );
@@ -138,41 +167,26 @@ fn compute_h_poly(_crs: &CRS, polys: &MulQAPPolys) -> DensePolynomial<Fr> {
 }
 
 #[allow(non_snake_case)]
-impl MulDigest {
-    pub fn setup(crs: &CRS, z0: Fr) -> Self {
+impl<E: Pairing> MulDigest<E> {
+    pub fn setup(crs: &CRS<E>, z0: E::ScalarField) -> Self {
         assert_eq!(
             crs.n, 4,
             "MulCircuit is currently hard-coded for n=4 (slots [x,y,z,1])"
         );
 
         // Selectors for x, y, z in w = [x, y, z, 1]
-        let s_x = vec![
-            Fr::from(1u32),
-            Fr::from(0u32),
-            Fr::from(0u32),
-            Fr::from(0u32),
-        ];
-        let s_y = vec![
-            Fr::from(0u32),
-            Fr::from(1u32),
-            Fr::from(0u32),
-            Fr::from(0u32),
-        ];
-        let s_z = vec![
-            Fr::from(0u32),
-            Fr::from(0u32),
-            Fr::from(1u32),
-            Fr::from(0u32),
-        ];
+        let s_x = Selector::one_hot(crs.n, 0);
+        let s_y = Selector::one_hot(crs.n, 1);
+        let s_z = Selector::one_hot(crs.n, 2);
 
         // Z(X) = X - 1 (Mul QAP vanishing poly on the single gate)
-        let z_poly = DensePolynomial::from_coefficients_vec(vec![-Fr::one(), Fr::one()]);
+        let z_poly = DensePolynomial::from_coefficients_vec(vec![-E::ScalarField::one(), E::ScalarField::one()]);
         let mul_z_tau_2 = crs.commit_poly_g2(z_poly.coeffs());
 
         // IIP vk's for x, y, z
-        let iip_vk_x = iip_digest(crs, &s_x);
-        let iip_vk_y = iip_digest(crs, &s_y);
-        let iip_vk_z = iip_digest(crs, &s_z);
+        let iip_vk_x = iip_digest_tagged(crs, &s_x, "iip_x");
+        let iip_vk_y = iip_digest_tagged(crs, &s_y, "iip_y");
+        let iip_vk_z = iip_digest_tagged(crs, &s_z, "iip_z");
 
         // Max degree bound for the SCS witness polynomial B(X) for w=[x,y,z,1]
         let d_bound = crs.n - 1; // with n=4, d_bound=3
@@ -180,54 +194,238 @@ impl MulDigest {
         // [τ^{N-d}]_1 in G1
         let tau_N_minus_d_1 = crs._g1_tau_pow(N - d_bound);
 
-        let lv = LVDigest {
-            iip_x: iip_vk_x,
-            iip_y: iip_vk_y,
-            iip_z: iip_vk_z,
-            one_idx: 3,
+        let lv = LVDigest::new(
+            crs,
+            iip_vk_x,
+            iip_vk_y,
+            iip_vk_z,
+            3,
             mul_z_tau_2,
-            instance_z: z0,
+            z0,
             d_bound,
             tau_N_minus_d_1,
-        };
+        )
+        .expect("MulDigest::setup: inconsistent LV digest");
 
         MulDigest { lv, s_x, s_y, s_z }
     }
+
+    /// Structural check of the selectors: s_x, s_y, s_z are one-hot vectors of
+    /// length crs.n on distinct slots, and one_idx is the constant-1 slot of
+    /// the [x, y, z, 1] layout, disjoint from all three.
+    pub fn validate(&self, crs: &CRS<E>) -> bool {
+        fn hot_index<F: Field>(s: &[F], n: usize) -> Option<usize> {
+            if s.len() != n {
+                return None;
+            }
+            let mut hot = s.iter().enumerate().filter(|(_, c)| !c.is_zero());
+            match (hot.next(), hot.next()) {
+                (Some((i, c)), None) if c.is_one() => Some(i),
+                _ => None,
+            }
+        }
+
+        let (ix, iy, iz) = match (
+            hot_index(&self.s_x, crs.n),
+            hot_index(&self.s_y, crs.n),
+            hot_index(&self.s_z, crs.n),
+        ) {
+            (Some(ix), Some(iy), Some(iz)) => (ix, iy, iz),
+            _ => return false,
+        };
+        let one_idx = self.lv.one_idx;
+
+        ix != iy && ix != iz && iy != iz
+            && one_idx < crs.n
+            && ![ix, iy, iz].contains(&one_idx)
+            && MulWitness { x: E::ScalarField::zero(), y: E::ScalarField::zero(), z: E::ScalarField::zero() }
+                .to_vec()[one_idx]
+                .is_one()
+    }
+
+    /// Sorted witness slots touched by s_x, s_y, s_z or one_idx, for auditing
+    /// that the digest covers the intended layout ([0, 1, 2, 3] for [x, y, z, 1]).
+    pub fn constrained_slots(&self) -> Vec<usize> {
+        let mut slots: Vec<usize> = [&self.s_x, &self.s_y, &self.s_z]
+            .iter()
+            .flat_map(|s| s.iter().enumerate().filter(|(_, c)| !c.is_zero()).map(|(i, _)| i))
+            .chain(std::iter::once(self.lv.one_idx))
+            .collect();
+        slots.sort_unstable();
+        slots.dedup();
+        slots
+    }
+}
+
+/// SquareGadget witness: proves z = x^2 on the Mul layout as w = [x, x, z, 1],
+/// i.e. the Mul QAP with A = B = w[in].
+#[derive(Clone, Debug)]
+pub struct SquareWitness<F: Field = Fr> {
+    pub x: F,
+    pub z: F,
+}
+
+impl<F: Field> SquareWitness<F> {
+    pub fn to_mul(&self) -> MulWitness<F> {
+        MulWitness { x: self.x, y: self.x, z: self.z }
+    }
+}
+
+/// MulDigest with the x = y tie row enabled, so a proof only verifies if
+/// both Mul inputs are the same slot value.
+#[derive(Clone)]
+pub struct SquareDigest<E: Pairing = Bn254> {
+    pub mul: MulDigest<E>,
+}
+
+impl<E: Pairing> SquareDigest<E> {
+    pub fn setup(crs: &CRS<E>, z0: E::ScalarField) -> Self {
+        let mut mul = MulDigest::setup(crs, z0);
+        mul.lv = mul.lv.tie_inputs();
+        SquareDigest { mul }
+    }
+}
+
+/// Prover for SquareGadget; the proof is an ordinary Mul proof over
+/// w = [x, x, z, 1] and is checked with `lv_verify(crs, &dg.mul.lv, ..)`.
+/// Panics unless x² = z.
+pub fn square_prove<E: Pairing>(crs: &CRS<E>, dg: &SquareDigest<E>, w: &SquareWitness<E::ScalarField>) -> MulProof<E> {
+    mul_prove(crs, &dg.mul, &w.to_mul())
+}
+
+/// The Mul relation x·y = z, checked directly on the witness. Pure field
+/// arithmetic; no proof or pairing involved.
+pub fn check_relation<F: Field>(w: &MulWitness<F>) -> bool {
+    w.x * w.y == w.z
 }
 
+/// The Mul QAP identity for `w`: P(1) = 0 and Z(X) = X - 1 divides P(X)
+/// exactly, i.e. the H(X) the prover would commit to exists. Pure field
+/// arithmetic; no proof or pairing involved.
+pub fn check_qap<E: Pairing>(_crs: &CRS<E>, w: &MulWitness<E::ScalarField>) -> bool {
+    let one = E::ScalarField::one();
+    let z_poly = DensePolynomial::from_coefficients_vec(vec![-one, one]);
+    let polys = build_mul_qap_polys(w, &z_poly);
+    let (_, r) = div_rem(&polys.p, &polys.z);
+    polys.p.evaluate(&one).is_zero() && r.coeffs().iter().all(|c| c.is_zero())
+}
+
+/// Ready-made (CRS, digest, witness) for the n=4 demo circuit, shared by
+/// benchmarks so they don't duplicate the setup wiring.
+pub fn bench_setup() -> (CRS, MulDigest, MulWitness) {
+    let crs = CRS::setup(rand::rng(), 4);
+
+    let x = Fr::from(12u32);
+    let y = Fr::from(17u32);
+    let w = MulWitness { x, y, z: x * y };
+
+    let dg = MulDigest::setup(&crs, w.z);
+    (crs, dg, w)
+}
+
+// The parallel prover shares these across rayon workers.
+#[cfg(feature = "parallel")]
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn check() {
+        assert_send_sync::<CRS>();
+        assert_send_sync::<MulDigest>();
+        assert_send_sync::<MulProvingKey>();
+        assert_send_sync::<crate::iip::IIPDigest>();
+        assert_send_sync::<crate::iip::IIPProof>();
+        assert_send_sync::<crate::nonzero::NonZeroProof>();
+    }
+    let _ = check;
+};
+
 /// Prover for MulCircuit: given witness w = [x,y,z,1], build LV proof.
-///
+/// Rebuilds the proving key each call; use `mul_prove_with_pk` when proving
+/// repeatedly against one digest.
+pub fn mul_prove<E: Pairing>(crs: &CRS<E>, dg: &MulDigest<E>, w: &MulWitness<E::ScalarField>) -> MulProof<E> {
+    // before MulProvingKey::setup, whose interpolation would trip first
+    check_sizes(crs, dg, &w.to_vec());
+    mul_prove_with_pk(crs, &MulProvingKey::setup(crs, dg), dg, w)
+}
+
+/// Fail here rather than deep inside iip_prove's length assertions.
+fn check_sizes<E: Pairing>(crs: &CRS<E>, dg: &MulDigest<E>, w_vec: &[E::ScalarField]) {
+    assert_eq!(
+        w_vec.len(), crs.n,
+        "mul_prove: witness layout [x,y,z,1] has {} slots but CRS domain has n={}",
+        w_vec.len(), crs.n
+    );
+    assert_eq!(
+        dg.lv.iip_z.n, crs.n,
+        "mul_prove: digest was built for n={} but CRS has n={}",
+        dg.lv.iip_z.n, crs.n
+    );
+}
+
+/// `mul_prove` reporting its start and duration to `m`.
+pub fn mul_prove_with_metrics<E: Pairing, M: Metrics + ?Sized>(
+    crs: &CRS<E>,
+    dg: &MulDigest<E>,
+    w: &MulWitness<E::ScalarField>,
+    m: &mut M,
+) -> MulProof<E> {
+    m.on_prove_start();
+    let t = Instant::now();
+    let pi = mul_prove(crs, dg, w);
+    m.on_prove_end(t.elapsed());
+    pi
+}
+
+/// `mul_prove` with the circuit-fixed polynomials taken from `pk`; only the
+/// witness-dependent commitments are computed here.
 #[allow(non_snake_case)]
-pub fn mul_prove(crs: &CRS, dg: &MulDigest, w: &MulWitness) -> MulProof {
+pub fn mul_prove_with_pk<E: Pairing>(
+    crs: &CRS<E>,
+    pk: &MulProvingKey<E::ScalarField>,
+    dg: &MulDigest<E>,
+    w: &MulWitness<E::ScalarField>,
+) -> MulProof<E> {
     let w_vec = w.to_vec();
+    check_sizes(crs, dg, &w_vec);
+    assert!(dg.validate(crs), "mul_prove: malformed MulDigest selectors or one_idx");
+
+    // B(X) interpolates w = [x,y,z,1] on D; commit it once in G2
+    let B_poly = crs.interpolate(&w_vec);
+    let w_tau_2 = crs.commit_poly_g2(B_poly.coeffs());
 
     // Three IIP proofs for selectors s_x, s_y, s_z (all over the same witness w)
-    let iip_pi_x = iip_prove(crs, &dg.s_x, &w_vec);
-    let iip_pi_y = iip_prove(crs, &dg.s_y, &w_vec);
-    let iip_pi_z = iip_prove(crs, &dg.s_z, &w_vec);
-    let nz_pi    = nonzero_prove(crs, &w_vec, dg.lv.one_idx);
+    // and the NonZero proof; independent, so run concurrently under `parallel`
+    let iip_x = || iip_prove_with_polys(crs, &dg.s_x, &w_vec, &pk.a_x, &B_poly, w_tau_2);
+    let iip_y = || iip_prove_with_polys(crs, &dg.s_y, &w_vec, &pk.a_y, &B_poly, w_tau_2);
+    let iip_z = || iip_prove_with_polys(crs, &dg.s_z, &w_vec, &pk.a_z, &B_poly, w_tau_2);
+    let nz    = || nonzero_prove(crs, &w_vec, dg.lv.one_idx);
+    #[cfg(feature = "parallel")]
+    let ((iip_pi_x, iip_pi_y), (iip_pi_z, nz_pi)) =
+        rayon::join(|| rayon::join(iip_x, iip_y), || rayon::join(iip_z, nz));
+    #[cfg(not(feature = "parallel"))]
+    let (iip_pi_x, iip_pi_y, iip_pi_z, nz_pi) = (iip_x(), iip_y(), iip_z(), nz());
+    // build_lv_coords rejects a proof whose two [B(τ)]_2 differ; catch that here
+    debug_assert_eq!(
+        iip_pi_z.w_tau_2, nz_pi.w_tau_2,
+        "mul_prove: IIP and NonZero witness commitments differ"
+    );
 
-    let polys   = build_mul_qap_polys(w);
+    let polys   = build_mul_qap_polys(w, &pk.mul_z);
     let commits = commit_mul_qap(crs, &polys);
 
     // --- MaxDeg for the IIP witness polynomial B(X) ---
-    // Rebuild B(X) as interpolation of w = [x,y,z,1] on D
-    let B_poly = crs.interpolate(&w_vec);
-    let shift = crs.N - dg.lv.d_bound; // N - d
-    let w_hat_poly = mul_by_xk(&B_poly, shift);
-    let w_hat_tau_1 = crs.commit_poly_g1(w_hat_poly.coeffs());
+    let w_hat_tau_1 = maxdeg_prove(crs, B_poly.coeffs(), dg.lv.d_bound)
+        .expect("mul_prove: B(X) exceeds the digest's MaxDeg bound");
 
     // Optional sanity checks
     #[cfg(debug_assertions)]
     {
         // P(1) = 0
-        let one = Fr::from(1u32);
+        let one = E::ScalarField::one();
         let p_at_1 = polys.p.evaluate(&one);
         debug_assert!(p_at_1.is_zero(), "QAP check failed: P(1) != 0");
 
         // If x*y=z, P(X) is the zero polynomial -> [P(τ)]_1 = identity
-        let gt_p =
-            <Bn254 as Pairing>::pairing(commits.p_tau_1, <Bn254 as Pairing>::G2::generator());
+        let gt_p = E::pairing(commits.p_tau_1, E::G2::generator());
         debug_assert!(
             gt_p.0.is_one(),
             "QAP GT check failed: [P(τ)]_1 not identity when x*y=z"
@@ -249,4 +447,155 @@ pub fn mul_prove(crs: &CRS, dg: &MulDigest, w: &MulWitness) -> MulProof {
     };
 
     MulProof { lv }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "mul_prove: witness layout [x,y,z,1] has 4 slots but CRS domain has n=8")]
+    fn mismatched_crs_size_is_reported_up_front() {
+        let (_, dg, w) = bench_setup();
+        let crs8: CRS = CRS::setup(rand::rng(), 8);
+        let _ = mul_prove(&crs8, &dg, &w);
+    }
+
+    #[test]
+    fn non_one_hot_selectors_are_rejected() {
+        let (crs, dg, _) = bench_setup();
+        assert!(dg.validate(&crs));
+
+        let mut two_hot = dg.clone();
+        two_hot.s_x[1] = Fr::one();
+        assert!(!two_hot.validate(&crs));
+
+        let mut scaled = dg.clone();
+        scaled.s_y[1] = Fr::from(2u64);
+        assert!(!scaled.validate(&crs));
+
+        let mut shared = dg.clone();
+        shared.s_y = shared.s_x.clone();
+        assert!(!shared.validate(&crs));
+
+        let mut short = dg.clone();
+        short.s_z.pop();
+        assert!(!short.validate(&crs));
+
+        let mut wrong_one = dg.clone();
+        wrong_one.lv.one_idx = 2;
+        assert!(!wrong_one.validate(&crs));
+    }
+
+    #[test]
+    fn one_proving_key_serves_many_witnesses() {
+        let (crs, dg, _) = bench_setup();
+        let pk = MulProvingKey::setup(&crs, &dg);
+        for (x, y) in [(12u64, 17u64), (4, 51), (1, 204), (204, 1)] {
+            let w = MulWitness { x: Fr::from(x), y: Fr::from(y), z: Fr::from(204u64) };
+            let pi = mul_prove_with_pk(&crs, &pk, &dg, &w);
+            assert!(crate::verifier::lv_verify(&crs, &dg.lv, &pi.lv), "x={} y={}", x, y);
+            assert_eq!(pi.lv.to_bytes(), mul_prove(&crs, &dg, &w).lv.to_bytes());
+        }
+    }
+
+    #[test]
+    fn demo_digest_constrains_every_slot() {
+        let (_, dg, _) = bench_setup();
+        assert_eq!(dg.constrained_slots(), vec![0, 1, 2, 3]);
+
+        // moving s_y onto x's slot leaves slot 1 unconstrained
+        let mut dup = dg.clone();
+        dup.s_y = dup.s_x.clone();
+        assert_eq!(dup.constrained_slots(), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn five_squared_is_25_not_24() {
+        use crate::verifier::lv_verify;
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let five = Fr::from(5u64);
+
+        let dg = SquareDigest::setup(&crs, Fr::from(25u64));
+        let pi = square_prove(&crs, &dg, &SquareWitness { x: five, z: Fr::from(25u64) });
+        assert!(lv_verify(&crs, &dg.mul.lv, &pi.lv));
+
+        // 24 is 4·6 but not a square: the Mul proof fails the x = y tie row
+        let bad = SquareDigest::setup(&crs, Fr::from(24u64));
+        let w = MulWitness { x: Fr::from(4u64), y: Fr::from(6u64), z: Fr::from(24u64) };
+        assert!(!lv_verify(&crs, &bad.mul.lv, &mul_prove(&crs, &bad.mul, &w).lv));
+        assert!(!lv_verify(&crs, &bad.mul.lv, &pi.lv));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mul QAP: P(X) is not divisible by Z(X); bad witness")]
+    fn five_squared_as_24_cannot_be_proved() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let dg = SquareDigest::setup(&crs, Fr::from(24u64));
+        let _ = square_prove(&crs, &dg, &SquareWitness { x: Fr::from(5u64), z: Fr::from(24u64) });
+    }
+
+    #[test]
+    fn iip_z_and_nonzero_share_the_witness_commitment() {
+        let (crs, dg, w) = bench_setup();
+        let pk = MulProvingKey::setup(&crs, &dg);
+        let pi = mul_prove_with_pk(&crs, &pk, &dg, &w).lv;
+        assert_eq!(pi.iip_z.w_tau_2, pi.nz.w_tau_2);
+
+        for w in [w, MulWitness { x: Fr::from(0u64), y: Fr::from(9u64), z: Fr::from(0u64) }] {
+            let dg = MulDigest::setup(&crs, w.z);
+            let pi = mul_prove(&crs, &dg, &w).lv;
+            assert_eq!(pi.iip_z.w_tau_2, pi.nz.w_tau_2);
+            assert_eq!(pi.iip_x.w_tau_2, pi.nz.w_tau_2);
+        }
+    }
+
+    #[test]
+    fn concurrent_gadget_proofs_equal_sequential_ones() {
+        // under --features parallel mul_prove runs the four gadgets via rayon::join
+        use crate::iip::{IIPProof, iip_prove};
+        use crate::nonzero::nonzero_prove;
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let w_vec = vec![w.x, w.y, w.z, Fr::from(1u64)];
+
+        let fields = |p: &IIPProof| (p.w_tau_2, [p.v_g1, p.QZ_tau_1, p.QX_tau_1, p.QX_hat_tau_1, p.v_hat_tau_1]);
+        for (name, got, s) in [("x", &pi.iip_x, &dg.s_x), ("y", &pi.iip_y, &dg.s_y), ("z", &pi.iip_z, &dg.s_z)] {
+            assert!(fields(got) == fields(&iip_prove(&crs, s, &w_vec)), "iip_{}", name);
+        }
+        let nz = nonzero_prove(&crs, &w_vec, dg.lv.one_idx);
+        assert_eq!(pi.nz.q0_tau_1, nz.q0_tau_1);
+        assert_eq!(pi.nz.w_tau_2, nz.w_tau_2);
+    }
+
+    #[test]
+    fn mul_snark_runs_over_bls12_381() {
+        use ark_bls12_381::{Bls12_381, Fr as BlsFr};
+        use crate::verifier::lv_verify;
+        let crs = CRS::<Bls12_381>::setup(rand::rng(), 4);
+        let w = MulWitness { x: BlsFr::from(12u64), y: BlsFr::from(17u64), z: BlsFr::from(204u64) };
+        let dg = MulDigest::<Bls12_381>::setup(&crs, w.z);
+        let pi = mul_prove(&crs, &dg, &w);
+        assert!(lv_verify(&crs, &dg.lv, &pi.lv));
+
+        let other = MulDigest::<Bls12_381>::setup(&crs, BlsFr::from(205u64));
+        assert!(!lv_verify(&crs, &other.lv, &pi.lv));
+    }
+
+    #[test]
+    fn relation_and_qap_checks_agree_on_witnesses() {
+        let (crs, _, good) = bench_setup();
+        assert!(check_relation(&good));
+        assert!(check_qap(&crs, &good));
+
+        let zero = MulWitness { x: Fr::from(0u64), y: Fr::from(9u64), z: Fr::from(0u64) };
+        assert!(check_relation(&zero) && check_qap(&crs, &zero));
+
+        for bad in [
+            MulWitness { x: Fr::from(12u64), y: Fr::from(17u64), z: Fr::from(205u64) },
+            MulWitness { x: Fr::from(0u64), y: Fr::from(9u64), z: Fr::from(1u64) },
+        ] {
+            assert!(!check_relation(&bad));
+            assert!(!check_qap(&crs, &bad));
+        }
+    }
+}
@@ -1,82 +1,82 @@
 //src/nonzero.rs
-use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+use ark_bn254::Bn254;
 use ark_ec::pairing::Pairing;
 use ark_ec::PrimeGroup;
-use ark_ff::{One, PrimeField, Zero};
-use ark_poly::{
-    univariate::DensePolynomial,
-    DenseUVPolynomial,
-    EvaluationDomain
-};
+use ark_ff::One;
+use ark_poly::DenseUVPolynomial;
 
-use crate::scs::CRS;
+use crate::scs::{CRS, VerifierKey, kzg_open_poly};
+use crate::verifier::gadget_pairing;
 
 /// We enforce that a dedicated slot w[idx_one] == 1.
 /// Prover returns [Q0(τ)]_1 for (B(X) - 1) = Q0(X)*(X - D[idx_one]).
 #[derive(Clone)]
-pub struct NonZeroProof {
-    pub q0_tau_1: G1Projective,
-    pub w_tau_2: G2Projective, // reuse same [B(τ)]_2 commitment
+pub struct NonZeroProof<E: Pairing = Bn254> {
+    pub q0_tau_1: E::G1,
+    pub w_tau_2: E::G2, // reuse same [B(τ)]_2 commitment
 }
 
-/// Synthetic division by (X - d) for polynomials with coefficients from lowest to highest degree.
-/// Given P(X) = sum_i c[i] X^i, returns (Q(X), r) such that:
-/// P(X) = (X - d) Q(X) + r
-fn divide_by_linear(poly: &DensePolynomial<Fr>, d: Fr) -> (DensePolynomial<Fr>, Fr) {
-    let coeffs = poly.coeffs();
-    let n = coeffs.len();
+/// Why `nonzero_prove_checked` refused to prove.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NonZeroError {
+    /// idx_one is not a slot of the domain. `domain.element` wraps modulo n,
+    /// so idx_one = n would otherwise silently open slot 0.
+    IdxOutOfRange { idx_one: usize, n: usize },
+    /// w does not have one entry per domain slot.
+    LengthMismatch { w: usize, n: usize },
+    /// w[idx_one] != 1, so (B(X) - 1) is not divisible by (X - d).
+    NotOne { idx_one: usize },
+}
 
-    if n == 0 {
-        return (DensePolynomial::zero(), Fr::zero());
-    }
-    if n == 1 {
-        // constant polynomial
-        return (DensePolynomial::zero(), coeffs[0]);
+impl std::fmt::Display for NonZeroError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonZeroError::IdxOutOfRange { idx_one, n } => {
+                write!(f, "idx_one={} out of range for n={}", idx_one, n)
+            }
+            NonZeroError::LengthMismatch { w, n } => write!(f, "witness len {} != n={}", w, n),
+            NonZeroError::NotOne { idx_one } => write!(f, "w[{}] is not 1", idx_one),
+        }
     }
+}
 
-    // Convert to descending coefficients a[0]..a[n] (degree n .. 0)
-    let a: Vec<Fr> = coeffs.iter().cloned().rev().collect();
+impl std::error::Error for NonZeroError {}
 
-    // Standard synthetic division in descending convention
-    let mut b = vec![Fr::zero(); n];
-    b[0] = a[0];
-    for i in 1..n {
-        // b_i = a_i + d * b_{i-1}
-        b[i] = a[i] + d * b[i - 1];
+/// `nonzero_prove` with its preconditions checked in every build profile.
+pub fn nonzero_prove_checked<E: Pairing>(
+    crs: &CRS<E>,
+    w: &[E::ScalarField],
+    idx_one: usize,
+) -> Result<NonZeroProof<E>, NonZeroError> {
+    if idx_one >= crs.n {
+        return Err(NonZeroError::IdxOutOfRange { idx_one, n: crs.n });
     }
-    let r = b[n - 1]; // remainder
-    let quot_desc = &b[..n - 1];
-
-    // Convert quotient back to ascending order
-    let q_coeffs: Vec<Fr> = quot_desc.iter().cloned().rev().collect();
-    let q = DensePolynomial::from_coefficients_vec(q_coeffs);
-
-    (q, r)
+    if w.len() != crs.n {
+        return Err(NonZeroError::LengthMismatch { w: w.len(), n: crs.n });
+    }
+    if !w[idx_one].is_one() {
+        return Err(NonZeroError::NotOne { idx_one });
+    }
+    Ok(nonzero_prove(crs, w, idx_one))
 }
 
 #[allow(non_snake_case)]
-pub fn nonzero_prove(crs: &CRS, w: &[Fr], idx_one: usize) -> NonZeroProof {
+pub fn nonzero_prove<E: Pairing>(crs: &CRS<E>, w: &[E::ScalarField], idx_one: usize) -> NonZeroProof<E> {
+    assert!(
+        idx_one < crs.n,
+        "nonzero_prove: idx_one={} out of range for n={}",
+        idx_one, crs.n
+    );
     // Build B(X) and commit
     let B = crs.interpolate(w);
     let w_tau_2 = crs.commit_poly_g2(B.coeffs());
 
     // KZG open at point D[idx_one] with claimed value 1:
-    // build Q0 = (B(X) - 1)/(X - d)
-    let d = crs.domain.element(idx_one);
-
-    // B_minus_1(X) = B(X) - 1
-    let mut c = B.coeffs().to_vec();
-    if c.is_empty() {
-        c.push(-Fr::one());
-    } else {
-        c[0] -= Fr::one();
-    }
-    let B_minus_1 = DensePolynomial::from_coefficients_vec(c);
+    // Q0 = (B(X) - 1)/(X - d)
+    let d = crs.domain_points()[idx_one];
+    let (value, q0_tau_1) = kzg_open_poly(crs, &B, d);
+    debug_assert!(value.is_one(), "B(X) - 1 not divisible by (X - d)");
 
-    let (Q0, rem) = divide_by_linear(&B_minus_1, d);
-    debug_assert!(rem.is_zero(), "B(X) - 1 not divisible by (X - d)");
-
-    let q0_tau_1 = crs.commit_poly_g1(Q0.coeffs());
     NonZeroProof { q0_tau_1, w_tau_2 }
 }
 
@@ -84,31 +84,58 @@ pub fn nonzero_prove(crs: &CRS, w: &[Fr], idx_one: usize) -> NonZeroProof {
 //
 // c8 = e(g1, w_tau_2)
 // c9 = e(q0_tau_1, (tau - d)_2)
-pub fn nonzero_verify(crs: &CRS, pi: &NonZeroProof, idx_one: usize) -> bool {
-    let d = crs.domain.element(idx_one);
+pub fn nonzero_verify<E: Pairing>(crs: &CRS<E>, pi: &NonZeroProof<E>, idx_one: usize) -> bool {
+    nonzero_verify_vk(&crs.verifier_key(), pi, idx_one)
+}
 
+/// `nonzero_verify` against a `VerifierKey` instead of the full CRS.
+pub fn nonzero_verify_vk<E: Pairing>(vk: &VerifierKey<E>, pi: &NonZeroProof<E>, idx_one: usize) -> bool {
+    // tau_minus_d_2 wraps idx_one modulo n; an out-of-range slot never verifies
+    if idx_one >= vk.n {
+        return false;
+    }
     // [τ]_2 - [d]_2
-    let tau_2 = crs.g2_tau_pow(1);
-    let d_g2 =
-        <Bn254 as Pairing>::G2::generator().mul_bigint(d.into_bigint());
-    let tau_minus_d_2 = tau_2 - d_g2;
+    let tau_minus_d_2 = vk.tau_minus_d_2(idx_one);
 
     // Check (in additive GT notation):
     // e(g1, [B(τ)]_2) = e(g1, [1]_2) + e([Q0(τ)]_1, [τ - d]_2)
     //
     // i.e. B(d) = 1 enforced via KZG opening
-    let lhs =
-        <Bn254 as Pairing>::pairing(<Bn254 as Pairing>::G1::generator(), pi.w_tau_2);
-    let term_q =
-        <Bn254 as Pairing>::pairing(pi.q0_tau_1, tau_minus_d_2);
-    let base =
-        <Bn254 as Pairing>::pairing(
-            <Bn254 as Pairing>::G1::generator(),
-            <Bn254 as Pairing>::G2::generator(),
-        );
+    let lhs = gadget_pairing::<E>(E::G1::generator(), pi.w_tau_2);
+    let term_q = gadget_pairing::<E>(pi.q0_tau_1, tau_minus_d_2);
+    let base = gadget_pairing::<E>(E::G1::generator(), E::G2::generator());
 
     // GT is modelled additively: product of pairings becomes sum in PairingOutput.
     let rhs = base + term_q;
 
     lhs == rhs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn idx_one_equal_to_n_is_rejected() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let w: Vec<Fr> = [1u64, 5, 7, 9].into_iter().map(Fr::from).collect();
+        assert_eq!(
+            nonzero_prove_checked(&crs, &w, crs.n).err(),
+            Some(NonZeroError::IdxOutOfRange { idx_one: 4, n: 4 })
+        );
+
+        // D[n] wraps to D[0], but a valid slot-0 proof does not verify as slot n
+        let pi = nonzero_prove_checked(&crs, &w, 0).unwrap();
+        assert!(nonzero_verify(&crs, &pi, 0));
+        assert!(!nonzero_verify(&crs, &pi, crs.n));
+    }
+
+    #[test]
+    #[should_panic(expected = "nonzero_prove: idx_one=4 out of range for n=4")]
+    fn unchecked_prove_panics_on_idx_one_equal_to_n() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let w = vec![Fr::one(); 4];
+        let _ = nonzero_prove(&crs, &w, 4);
+    }
+}
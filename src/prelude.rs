@@ -0,0 +1,64 @@
+//! The types and functions needed for the common prove / encrypt / decrypt
+//! flow, in one import.
+//!
+//! ```
+//! use ark_bn254::Fr;
+//! use we_snark::prelude::*;
+//!
+//! let mut rng = rand::rng();
+//! let crs: CRS = CRS::setup(&mut rng, 4);
+//!
+//! // Prover: x·y = z
+//! let (x, y) = (Fr::from(12u32), Fr::from(17u32));
+//! let w = MulWitness { x, y, z: x * y };
+//! let dg = MulDigest::setup(&crs, w.z);
+//! let pi = mul_prove(&crs, &dg, &w);
+//! assert!(lv_verify(&crs, &dg.lv, &pi.lv));
+//!
+//! // Encryptor: needs only the digest, not the witness
+//! let params = lv_public_linear_params(&crs, &dg.lv).unwrap();
+//! let (hdr, key) = lv_make_header(&params, &crs, &mut rng);
+//! let nonce = [7u8; 12];
+//! let mut ct = b"hello".to_vec();
+//! let tag = aead_encrypt(&crs, &params, &hdr, &key, nonce, DEFAULT_TAG_LEN, &mut ct);
+//!
+//! // Decryptor: the proof opens the ciphertext
+//! let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi.lv, nonce, &mut ct, &tag).unwrap();
+//! assert_eq!(pt, b"hello");
+//! ```
+
+pub use crate::mul_snark::{MulDigest, MulWitness, mul_prove};
+pub use crate::scs::CRS;
+pub use crate::verifier::lv_verify;
+pub use crate::we::{
+    DEFAULT_TAG_LEN, aead_encrypt, decrypt_with_lv_header, lv_make_header, lv_public_linear_params,
+};
+
+#[cfg(test)]
+mod tests {
+    // Only the prelude is imported: the flow must not need anything else.
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn prelude_covers_the_round_trip_and_a_wrong_proof_fails() {
+        let mut rng = rand::rng();
+        let crs: CRS = CRS::setup(&mut rng, 4);
+        let w = MulWitness { x: Fr::from(6u32), y: Fr::from(7u32), z: Fr::from(42u32) };
+        let dg = MulDigest::setup(&crs, w.z);
+        let pi = mul_prove(&crs, &dg, &w);
+        assert!(lv_verify(&crs, &dg.lv, &pi.lv));
+
+        let params = lv_public_linear_params(&crs, &dg.lv).unwrap();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rng);
+        let mut ct = b"prelude".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut ct);
+        let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi.lv, [1; 12], &mut ct.clone(), &tag);
+        assert_eq!(pt.unwrap(), b"prelude");
+
+        // a valid proof of another statement does not open it
+        let w2 = MulWitness { x: Fr::from(2u32), y: Fr::from(3u32), z: Fr::from(6u32) };
+        let pi2 = mul_prove(&crs, &MulDigest::setup(&crs, w2.z), &w2);
+        assert!(decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi2.lv, [1; 12], &mut ct, &tag).is_err());
+    }
+}
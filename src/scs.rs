@@ -1,52 +1,112 @@
 //src/scs.rs
-use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
-use ark_ec::{PrimeGroup, pairing::Pairing};
+use ark_bn254::Bn254;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, VariableBaseMSM, pairing::Pairing};
 use ark_ff::{Field, One, PrimeField, Zero};
 use ark_poly::{
     DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain, univariate::DensePolynomial,
 };
 use rand::Rng;
+use crate::transcript::Transcript;
+use std::sync::OnceLock;
 
+/// Powers-of-τ SRS over the pairing `E`; BN254 unless stated otherwise, so
+/// `CRS` in a type position is the BN254 CRS the demo and the WE layer use.
+/// In expression position name the curve (`CRS::<Bn254>::setup`) or
+/// annotate the binding, since `E` cannot be inferred from `n` alone.
 #[allow(non_snake_case)]
-pub struct CRS {
-    pub n: usize,                            // domain size (power of two)
-    pub n_inv: Fr,                           // 1/n (y* in Construction 6 when x* = 0)
-    pub g1_pows: Vec<G1Projective>,          // [tau^0]_1 .. [tau^N]_1
-    pub g2_pows: Vec<G2Projective>,          // [tau^0]_2 .. [tau^N]_2
-    pub N: usize,                            // max degree supported by CRS
-    pub vanishing_coeffs: Vec<Fr>,           // coeffs of Z_D(X)
-    pub domain: GeneralEvaluationDomain<Fr>, // D (roots of unity)
+pub struct CRS<E: Pairing = Bn254> {
+    pub n: usize,                                        // domain size (power of two)
+    pub n_inv: E::ScalarField,                           // 1/n (y* in Construction 6 when x* = 0)
+    pub g1_pows: Vec<E::G1Affine>,                       // [tau^0]_1 .. [tau^N]_1, affine MSM bases
+    pub g2_pows: Vec<E::G2Affine>,                       // [tau^0]_2 .. [tau^N]_2, affine MSM bases
+    pub N: usize,                                        // max degree supported by CRS
+    pub vanishing_coeffs: Vec<E::ScalarField>,           // coeffs of Z_D(X)
+    pub Z_tau_1: E::G1,                                  // [Z_D(τ)]_1, cached at setup
+    pub Z_tau_2: E::G2,                                  // [Z_D(τ)]_2, cached at setup
+    pub domain: GeneralEvaluationDomain<E::ScalarField>, // D (roots of unity)
+    pub gamma_g1: E::G1,                                 // [γ]_1, blinding base for hiding commitments
+    domain_points: Vec<E::ScalarField>,                  // D[0] .. D[n-1]
+    lagrange_g1: OnceLock<Vec<E::G1>>,                   // [L_i(τ)]_1, built on first use
 }
+/// The slice of the CRS that `lv_verify` actually reads: sizes, the domain
+/// and [τ]_2. Everything else a verifier needs lives in the digest, so this
+/// can be shipped instead of the full power tables.
 #[allow(non_snake_case)]
-impl CRS {
-    pub fn setup<R: Rng>(mut rng: R, n: usize) -> Self {
+#[derive(Clone)]
+pub struct VerifierKey<E: Pairing = Bn254> {
+    pub n: usize,
+    pub N: usize,
+    pub domain: GeneralEvaluationDomain<E::ScalarField>,
+    pub tau_2: E::G2, // [τ]_2
+}
+
+impl<E: Pairing> VerifierKey<E> {
+    /// [τ - D[idx]]_2, the NonZero opening base.
+    pub fn tau_minus_d_2(&self, idx: usize) -> E::G2 {
+        let d = self.domain.element(idx);
+        self.tau_2 - E::G2::generator().mul_bigint(d.into_bigint())
+    }
+}
+
+#[allow(non_snake_case)]
+impl<E: Pairing> CRS<E> {
+    /// n = 1 is a valid degenerate domain D = {1}, Z(X) = X - 1: IIP and
+    /// NonZero work unchanged (A·B - v/y* is then identically zero). The Mul
+    /// demo circuit itself needs n = 4.
+    pub fn setup<R: Rng>(rng: R, n: usize) -> Self {
+        assert!(n > 0, "CRS::setup: domain size n must be at least 1");
         // n must be power-of-two
-        let domain = GeneralEvaluationDomain::<Fr>::new(n).expect("radix-2 domain");
-        let n_inv = Fr::from(n as u64).inverse().unwrap(); // y* = 1/n at x* = 0
-        let tau = Fr::from(rng.random::<u128>()); // trapdoor, local only
-        // choose N >= 2n so we have indices N-n+2 and N (as in Construction 6)
-        let N = 2 * n + 4;
-
-        let mut g1_pows = Vec::with_capacity(N + 1);
-        let mut g2_pows = Vec::with_capacity(N + 1);
-        let g1 = <Bn254 as Pairing>::G1::generator();
-        let g2 = <Bn254 as Pairing>::G2::generator();
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(n).expect("radix-2 domain");
+        Self::setup_with_domain(rng, domain)
+    }
+
+    /// `setup` over a caller-built domain, e.g. a coset h·⟨ω⟩ from
+    /// `GeneralEvaluationDomain::new_coset`. Z(X) becomes X^n - h^n and
+    /// interpolation, the Lagrange table and NonZero's D[idx] all follow
+    /// the coset points.
+    pub fn setup_with_domain<R: Rng>(rng: R, domain: GeneralEvaluationDomain<E::ScalarField>) -> Self {
+        // smallest N for the Mul circuit: MaxDeg bound d = n-1, with Mul QAP
+        let N = Self::min_degree(domain.size());
+        Self::setup_with_degree(rng, domain, N)
+    }
 
-        let mut tpow = Fr::one();
+    /// `setup_with_domain` with an explicit max degree N, at least
+    /// `min_degree(n)`; a larger N suits gadget sets whose `required_N` is
+    /// higher.
+    pub fn setup_with_degree<R: Rng>(mut rng: R, domain: GeneralEvaluationDomain<E::ScalarField>, N: usize) -> Self {
+        let n = domain.size();
+        let n_inv = E::ScalarField::from(n as u64).inverse().unwrap(); // y* = 1/n at x* = 0 (also on a coset)
+        let tau = E::ScalarField::from(rng.random::<u128>()); // trapdoor, local only
+        let gamma = E::ScalarField::from(rng.random::<u128>()); // independent blinding trapdoor, discarded
+        let N_min = Self::min_degree(n);
+        assert!(
+            N >= N_min,
+            "CRS::setup: n={} needs N >= {} (Z(X) has degree n, A·B degree 2n-2; MaxDeg uses X^(N-d), d <= n-1), got N={}",
+            n, N_min, N
+        );
+
+        let mut g1_proj = Vec::with_capacity(N + 1);
+        let mut g2_proj = Vec::with_capacity(N + 1);
+        let g1 = E::G1::generator();
+        let g2 = E::G2::generator();
+
+        let mut tpow = E::ScalarField::one();
         for _ in 0..=N {
-            g1_pows.push(g1.mul_bigint(tpow.into_bigint()));
-            g2_pows.push(g2.mul_bigint(tpow.into_bigint()));
+            g1_proj.push(g1.mul_bigint(tpow.into_bigint()));
+            g2_proj.push(g2.mul_bigint(tpow.into_bigint()));
             tpow *= tau;
         }
 
-        // Convert to DensePolynomial to get coefficients
-        let Z_dense = DensePolynomial::from_coefficients_vec({
-            let mut coeffs = vec![Fr::zero(); n + 1];
-            coeffs[0] = -Fr::one();
-            coeffs[n] = Fr::one();
-            coeffs
-        });
+        // Z_D(X) = X^n - h^n (h = 1 for the plain subgroup)
+        let Z_dense: DensePolynomial<E::ScalarField> = domain.vanishing_polynomial().into();
         let vanishing_coeffs = Z_dense.coeffs().to_vec();
+        // One batched inversion per table instead of one per commitment term
+        let g1_pows = E::G1::normalize_batch(&g1_proj);
+        let g2_pows = E::G2::normalize_batch(&g2_proj);
+        // Z only depends on the CRS, so commit it once here rather than per digest
+        let Z_tau_1 = E::G1::msm_unchecked(&g1_pows[..vanishing_coeffs.len()], &vanishing_coeffs);
+        let Z_tau_2 = E::G2::msm_unchecked(&g2_pows[..vanishing_coeffs.len()], &vanishing_coeffs);
+
         CRS {
             n,
             n_inv,
@@ -54,12 +114,39 @@ impl CRS {
             g2_pows,
             N,
             vanishing_coeffs,
+            Z_tau_1,
+            Z_tau_2,
             domain,
+            gamma_g1: g1.mul_bigint(gamma.into_bigint()),
+            domain_points: domain.elements().collect(),
+            lagrange_g1: OnceLock::new(),
+        }
+    }
+
+    /// Smallest N the Mul circuit can work with on a domain of size n:
+    /// `required_N` with the MaxDeg bound d = n-1 and the Mul QAP.
+    pub fn min_degree(n: usize) -> usize {
+        Self::required_N(n, n.saturating_sub(1), true)
+    }
+
+    /// Smallest N supporting a gadget set on a domain of size n:
+    /// - Z(X) = X^n - h^n is committed in both groups: N >= n;
+    /// - IIP: Q_X (degree <= n-1) is shifted by X^{N-n+1} and v by X^N,
+    ///   which stay within [τ^0..τ^N] for any N >= n-1;
+    /// - MaxDeg: [τ^{N-d}]_1 must exist, N >= d_bound;
+    /// - Mul QAP: [P(τ)]_1 with P = A·B - C has degree 2n-2 for QAP
+    ///   polynomials of degree <= n-1 (conservative for the one-gate Mul
+    ///   circuit, whose A, B, C are constants).
+    pub fn required_N(n: usize, d_bound: usize, has_mul: bool) -> usize {
+        let mut N = n.max(d_bound);
+        if has_mul {
+            N = N.max((2 * n).saturating_sub(2));
         }
+        N
     }
 
     /// Commit polynomial in G1: returns [F(τ)]_1 = Σ f_j [τ^j]_1
-    pub fn commit_poly_g1(&self, coeffs: &[Fr]) -> G1Projective {
+    pub fn commit_poly_g1(&self, coeffs: &[E::ScalarField]) -> E::G1 {
         // find last non-zero
         let max = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
         assert!(
@@ -68,20 +155,89 @@ impl CRS {
             max,
             self.N
         );
-        coeffs
-            .iter()
-            .take(max + 1)
-            .enumerate()
-            .fold(G1Projective::zero(), |acc, (j, c)| {
-                if c.is_zero() {
-                    acc
-                } else {
-                    acc + self.g1_pows[j].mul_bigint((*c).into_bigint())
-                }
-            })
+        let len = coeffs.len().min(max + 1);
+        E::G1::msm_unchecked(&self.g1_pows[..len], &coeffs[..len])
+    }
+
+    /// `commit_poly_g1` as a sum of MSMs over `chunk` coefficients at a time,
+    /// so the MSM's internal buffers stay O(chunk) instead of O(deg).
+    pub fn commit_poly_g1_chunked(&self, coeffs: &[E::ScalarField], chunk: usize) -> E::G1 {
+        assert!(chunk > 0, "commit_poly_g1_chunked: chunk must be positive");
+        let max = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
+        assert!(
+            max <= self.N,
+            "commit_poly_g1_chunked: deg={} exceeds CRS.N={}",
+            max,
+            self.N
+        );
+        let len = coeffs.len().min(max + 1);
+        coeffs[..len]
+            .chunks(chunk)
+            .zip(self.g1_pows[..len].chunks(chunk))
+            .map(|(c, g)| E::G1::msm_unchecked(g, c))
+            .sum()
+    }
+
+    /// `commit_poly_g1` over coefficients streamed from an iterator (e.g.
+    /// read from disk): at most 4096 of them are buffered at a time, each
+    /// batch MSM'd against the matching slice of the powers.
+    pub fn commit_poly_g1_iter(&self, coeffs: impl IntoIterator<Item = E::ScalarField>) -> E::G1 {
+        const CHUNK: usize = 1 << 12;
+        let mut acc = E::G1::zero();
+        let mut buf = Vec::with_capacity(CHUNK);
+        let mut start = 0;
+        for (i, c) in coeffs.into_iter().enumerate() {
+            if i > self.N {
+                assert!(c.is_zero(), "commit_poly_g1_iter: deg={} exceeds CRS.N={}", i, self.N);
+                continue;
+            }
+            buf.push(c);
+            if buf.len() == CHUNK {
+                acc += E::G1::msm_unchecked(&self.g1_pows[start..start + CHUNK], &buf);
+                start += CHUNK;
+                buf.clear();
+            }
+        }
+        if !buf.is_empty() {
+            acc += E::G1::msm_unchecked(&self.g1_pows[start..start + buf.len()], &buf);
+        }
+        acc
+    }
+
+    /// Hiding (Pedersen-style) commitment [F(τ)]_1 + r·[γ]_1. With r
+    /// uniform it reveals nothing about F, so candidate polynomials cannot be
+    /// tested against it; it opens by revealing (F, r), see `open_hiding_g1`.
+    ///
+    /// Only use it for commitments that are opened this way. Every LV/IIP
+    /// pairing equation assumes C = [F(τ)]_1 exactly: a blinded C leaves an
+    /// e(r·[γ]_1, ·) term that nothing in the proof cancels, so feeding one
+    /// into iip_digest, the Mul columns or MaxDeg makes verification fail.
+    pub fn commit_poly_g1_hiding(&self, coeffs: &[E::ScalarField], r: E::ScalarField) -> E::G1 {
+        self.commit_poly_g1(coeffs) + self.gamma_g1.mul_bigint(r.into_bigint())
+    }
+
+    /// Check an opening (F, r) of a `commit_poly_g1_hiding` commitment.
+    pub fn open_hiding_g1(&self, commitment: &E::G1, coeffs: &[E::ScalarField], r: E::ScalarField) -> bool {
+        *commitment == self.commit_poly_g1_hiding(coeffs, r)
     }
+
+    /// [X^shift · F(τ)]_1 = Σ f_j [τ^{j+shift}]_1, read straight off the high
+    /// powers instead of committing a zero-padded mul_by_xk(F, shift).
+    pub fn commit_shifted(&self, coeffs: &[E::ScalarField], shift: usize) -> E::G1 {
+        let max = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
+        assert!(
+            max + shift <= self.N,
+            "commit_shifted: deg={} + shift={} exceeds CRS.N={}",
+            max,
+            shift,
+            self.N
+        );
+        let len = coeffs.len().min(max + 1);
+        E::G1::msm_unchecked(&self.g1_pows[shift..shift + len], &coeffs[..len])
+    }
+
     /// Commit polynomial in G2: returns [F(τ)]_2 = Σ f_j [τ^j]_2
-    pub fn commit_poly_g2(&self, coeffs: &[Fr]) -> G2Projective {
+    pub fn commit_poly_g2(&self, coeffs: &[E::ScalarField]) -> E::G2 {
         let max = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
         assert!(
             max <= self.N,
@@ -89,21 +245,78 @@ impl CRS {
             max,
             self.N
         );
-        coeffs
+        let len = coeffs.len().min(max + 1);
+        E::G2::msm_unchecked(&self.g2_pows[..len], &coeffs[..len])
+    }
+
+    /// ([F(τ)]_1, [F(τ)]_2) in one pass: the degree scan and the scalar
+    /// bigint conversion are shared by both MSMs.
+    pub fn commit_poly_both(&self, coeffs: &[E::ScalarField]) -> (E::G1, E::G2) {
+        let max = coeffs.iter().rposition(|c| !c.is_zero()).unwrap_or(0);
+        assert!(
+            max <= self.N,
+            "commit_poly_both: deg={} exceeds CRS.N={}",
+            max,
+            self.N
+        );
+        let len = coeffs.len().min(max + 1);
+        let bigints: Vec<_> = coeffs[..len].iter().map(|c| c.into_bigint()).collect();
+        (
+            E::G1::msm_bigint(&self.g1_pows[..len], &bigints),
+            E::G2::msm_bigint(&self.g2_pows[..len], &bigints),
+        )
+    }
+
+    /// Lagrange basis commitments [L_0(τ)]_1 .. [L_{n-1}(τ)]_1 over D.
+    /// Since L_i(X) = (1/n) Σ_j ω^{-ij} X^j, the table is one inverse FFT of
+    /// [τ^0]_1 .. [τ^{n-1}]_1; it is computed once and cached.
+    /// On a coset h·⟨ω⟩ the coefficients carry an extra h^{-j}, applied to
+    /// [τ^j]_1 before the subgroup IFFT.
+    pub fn lagrange_table(&self) -> &[E::G1] {
+        self.lagrange_g1.get_or_init(|| {
+            let powers: Vec<E::G1> = self.g1_pows[..self.n].iter().map(|p| p.into_group()).collect();
+            let h_inv = self.domain.coset_offset_inv();
+            if h_inv.is_one() {
+                return self.domain.ifft(&powers);
+            }
+            let mut h_pow = E::ScalarField::one();
+            let scaled: Vec<E::G1> = powers
+                .iter()
+                .map(|p| {
+                    let q = p.mul_bigint(h_pow.into_bigint());
+                    h_pow *= h_inv;
+                    q
+                })
+                .collect();
+            GeneralEvaluationDomain::<E::ScalarField>::new(self.n)
+                .expect("radix-2 domain")
+                .ifft(&scaled)
+        })
+    }
+
+    /// [Σ e_i L_i(τ)]_1 for evaluations `evals` on D, via the Lagrange table
+    /// (equals commit_poly_g1(interpolate(evals)) without the IFFT).
+    pub fn commit_evals_g1(&self, evals: &[E::ScalarField]) -> E::G1 {
+        assert_eq!(evals.len(), self.n);
+        evals
             .iter()
-            .take(max + 1)
-            .enumerate()
-            .fold(G2Projective::zero(), |acc, (j, c)| {
-                if c.is_zero() {
+            .zip(self.lagrange_table())
+            .fold(E::G1::zero(), |acc, (e, l)| {
+                if e.is_zero() {
                     acc
                 } else {
-                    acc + self.g2_pows[j].mul_bigint((*c).into_bigint())
+                    acc + l.mul_bigint(e.into_bigint())
                 }
             })
     }
 
+    /// The domain points D[0] .. D[n-1], computed once in setup.
+    pub fn domain_points(&self) -> &[E::ScalarField] {
+        &self.domain_points
+    }
+
     /// Interpolate evaluations `vals` on D to DensePolynomial coeffs
-    pub fn interpolate(&self, evals: &[Fr]) -> DensePolynomial<Fr> {
+    pub fn interpolate(&self, evals: &[E::ScalarField]) -> DensePolynomial<E::ScalarField> {
         assert_eq!(evals.len(), self.n);
         // inverse FFT to get coeffs over monomial basis
         let mut v = evals.to_vec();
@@ -111,13 +324,445 @@ impl CRS {
         DensePolynomial::from_coefficients_vec(v)
     }
 
+    /// `interpolate` over several evaluation vectors. arkworks recomputes
+    /// the IFFT twiddles per call, so the saving is one validation pass and,
+    /// with the `parallel` feature, running the IFFTs concurrently.
+    pub fn interpolate_batch(&self, evals: &[Vec<E::ScalarField>]) -> Vec<DensePolynomial<E::ScalarField>> {
+        assert!(
+            evals.iter().all(|e| e.len() == self.n),
+            "interpolate_batch: every evaluation vector needs n={} entries",
+            self.n
+        );
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            evals.par_iter().map(|e| self.interpolate(e)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            evals.iter().map(|e| self.interpolate(e)).collect()
+        }
+    }
+
+    /// Extract the verifier-side parameters (no power tables).
+    pub fn verifier_key(&self) -> VerifierKey<E> {
+        VerifierKey {
+            n: self.n,
+            N: self.N,
+            domain: self.domain,
+            tau_2: self.g2_tau_pow(1),
+        }
+    }
+
+    /// Cheap check that `other` was produced from the same trapdoor: equal
+    /// sizes and equal powers at the indices the gadgets read directly
+    /// (τ^0, τ^1, τ^n, τ^{N-n+1}, τ^N) in both groups. Use `fingerprint` for
+    /// a full comparison.
+    pub fn is_compatible(&self, other: &CRS<E>) -> bool {
+        if self.n != other.n || self.N != other.N
+            || self.g1_pows.len() != other.g1_pows.len()
+            || self.g2_pows.len() != other.g2_pows.len()
+            || self.gamma_g1 != other.gamma_g1
+        {
+            return false;
+        }
+        [0, 1, self.n, self.N - self.n + 1, self.N]
+            .iter()
+            .all(|&k| self.g1_pows[k] == other.g1_pows[k] && self.g2_pows[k] == other.g2_pows[k])
+    }
+
+    /// SHA-256 over n, N, both power tables and [γ]_1.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut t = Transcript::new(b"we-snark/crs");
+        t.append_u64(b"n", self.n as u64);
+        t.append_u64(b"N", self.N as u64);
+        for g in &self.g1_pows {
+            t.append_serializable(b"g1", g);
+        }
+        for g in &self.g2_pows {
+            t.append_serializable(b"g2", g);
+        }
+        t.append_serializable(b"gamma", &self.gamma_g1);
+        t.challenge_bytes(b"fingerprint")
+    }
+
     /// Convenience: [τ^k]_2 in G2
-    pub fn g2_tau_pow(&self, k: usize) -> G2Projective {
-        self.g2_pows[k]
+    pub fn g2_tau_pow(&self, k: usize) -> E::G2 {
+        self.g2_pows[k].into_group()
     }
 
     /// Convenience: [τ^k]_1 in G1
-    pub fn _g1_tau_pow(&self, k: usize) -> G1Projective {
-        self.g1_pows[k]
+    pub fn _g1_tau_pow(&self, k: usize) -> E::G1 {
+        self.g1_pows[k].into_group()
+    }
+}
+
+/// Synthetic division by (X - d) for polynomials with coefficients from lowest to highest degree.
+/// Given P(X) = sum_i c[i] X^i, returns (Q(X), r) such that:
+/// P(X) = (X - d) Q(X) + r
+fn divide_by_linear<F: Field>(poly: &DensePolynomial<F>, d: F) -> (DensePolynomial<F>, F) {
+    let coeffs = poly.coeffs();
+    let n = coeffs.len();
+
+    if n == 0 {
+        return (DensePolynomial::zero(), F::zero());
+    }
+    if n == 1 {
+        // constant polynomial
+        return (DensePolynomial::zero(), coeffs[0]);
+    }
+
+    // Convert to descending coefficients a[0]..a[n] (degree n .. 0)
+    let a: Vec<F> = coeffs.iter().cloned().rev().collect();
+
+    // Standard synthetic division in descending convention
+    let mut b = vec![F::zero(); n];
+    b[0] = a[0];
+    for i in 1..n {
+        // b_i = a_i + d * b_{i-1}
+        b[i] = a[i] + d * b[i - 1];
+    }
+    let r = b[n - 1]; // remainder
+    let quot_desc = &b[..n - 1];
+
+    // Convert quotient back to ascending order
+    let q_coeffs: Vec<F> = quot_desc.iter().cloned().rev().collect();
+    let q = DensePolynomial::from_coefficients_vec(q_coeffs);
+
+    (q, r)
+}
+
+/// KZG opening at an arbitrary point z (on or off D) of the polynomial with
+/// evaluations `evals` on D: returns (F(z), [Q(τ)]_1) with
+/// Q(X) = (F(X) - F(z)) / (X - z).
+pub fn kzg_open<E: Pairing>(crs: &CRS<E>, evals: &[E::ScalarField], z: E::ScalarField) -> (E::ScalarField, E::G1) {
+    kzg_open_poly(crs, &crs.interpolate(evals), z)
+}
+
+/// `kzg_open` for a polynomial already in coefficient form.
+pub fn kzg_open_poly<E: Pairing>(
+    crs: &CRS<E>,
+    poly: &DensePolynomial<E::ScalarField>,
+    z: E::ScalarField,
+) -> (E::ScalarField, E::G1) {
+    // F(X) = (X - z) Q(X) + F(z)
+    let (q, value) = divide_by_linear(poly, z);
+    (value, crs.commit_poly_g1(q.coeffs()))
+}
+
+/// Check a `kzg_open` proof against C = [F(τ)]_1:
+/// e(C - [v]_1, [1]_2) = e([Q(τ)]_1, [τ - z]_2).
+pub fn kzg_verify<E: Pairing>(
+    vk: &VerifierKey<E>,
+    commitment: E::G1,
+    z: E::ScalarField,
+    value: E::ScalarField,
+    proof: E::G1,
+) -> bool {
+    let g1 = E::G1::generator();
+    let g2 = E::G2::generator();
+    let lhs = E::pairing(commitment - g1.mul_bigint(value.into_bigint()), g2);
+    let rhs = E::pairing(proof, vk.tau_2 - g2.mul_bigint(z.into_bigint()));
+    lhs == rhs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::{MulDigest, MulWitness, mul_prove};
+    use crate::verifier::lv_verify;
+    use ark_bn254::Fr;
+
+    fn domain(n: usize) -> GeneralEvaluationDomain<Fr> {
+        GeneralEvaluationDomain::new(n).unwrap()
+    }
+
+    #[test]
+    fn required_n_is_enough_for_mul() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        assert_eq!(crs.N, CRS::<Bn254>::required_N(4, 3, true));
+
+        let w = MulWitness { x: Fr::from(3u64), y: Fr::from(5u64), z: Fr::from(15u64) };
+        let dg = MulDigest::setup(&crs, w.z);
+        let pi = mul_prove(&crs, &dg, &w);
+        assert!(lv_verify(&crs, &dg.lv, &pi.lv));
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn min_degree_is_accepted_at_the_boundary() {
+        for n in [1, 2, 4, 8] {
+            let N = CRS::<Bn254>::min_degree(n);
+            let crs = CRS::<Bn254>::setup_with_degree(rand::rng(), domain(n), N);
+            assert_eq!(crs.N, N);
+            // the hatted IIP / MaxDeg bases exist
+            let _ = crs.g2_tau_pow(N - n + 1);
+            let _ = crs._g1_tau_pow(N - (n - 1));
+        }
+
+        let crs = CRS::<Bn254>::setup_with_degree(rand::rng(), domain(4), CRS::<Bn254>::min_degree(4));
+        let w = MulWitness { x: Fr::from(7u64), y: Fr::from(6u64), z: Fr::from(42u64) };
+        let dg = MulDigest::setup(&crs, w.z);
+        assert!(lv_verify(&crs, &dg.lv, &mul_prove(&crs, &dg, &w).lv));
+    }
+
+    #[test]
+    #[should_panic(expected = "needs N >= 6")]
+    fn min_degree_minus_one_panics() {
+        let _ = CRS::<Bn254>::setup_with_degree(rand::rng(), domain(4), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs N >=")]
+    fn one_below_required_n_is_rejected() {
+        let n = 4;
+        let _ = CRS::<Bn254>::setup_with_degree(rand::rng(), domain(n), CRS::<Bn254>::min_degree(n) - 1);
+    }
+
+    #[test]
+    fn affine_tables_match_projective_commitments() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let coeffs: Vec<Fr> = (1..=crs.N as u64 + 1).map(Fr::from).collect();
+
+        let g1 = <Bn254 as Pairing>::G1::generator();
+        let g2 = <Bn254 as Pairing>::G2::generator();
+        let proj_g1: Vec<_> = (0..=crs.N).map(|k| crs._g1_tau_pow(k)).collect();
+        let proj_g2: Vec<_> = (0..=crs.N).map(|k| crs.g2_tau_pow(k)).collect();
+        assert_eq!(proj_g1[0], g1);
+        assert_eq!(proj_g2[0], g2);
+
+        let naive_g1: <Bn254 as Pairing>::G1 =
+            coeffs.iter().zip(&proj_g1).map(|(c, p)| p.mul_bigint(c.into_bigint())).sum();
+        let naive_g2: <Bn254 as Pairing>::G2 =
+            coeffs.iter().zip(&proj_g2).map(|(c, p)| p.mul_bigint(c.into_bigint())).sum();
+        assert_eq!(crs.commit_poly_g1(&coeffs), naive_g1);
+        assert_eq!(crs.commit_poly_g2(&coeffs), naive_g2);
+        assert_eq!(crs.commit_poly_both(&coeffs), (naive_g1, naive_g2));
+        assert_eq!(crs.commit_poly_g1_chunked(&coeffs, 2), naive_g1);
+        assert_eq!(crs.commit_poly_g1_iter(coeffs.iter().copied()), naive_g1);
+
+        let naive_shift: <Bn254 as Pairing>::G1 =
+            coeffs[..3].iter().zip(&proj_g1[2..]).map(|(c, p)| p.mul_bigint(c.into_bigint())).sum();
+        assert_eq!(crs.commit_shifted(&coeffs[..3], 2), naive_shift);
+
+        let evals: Vec<Fr> = (0..4u64).map(|i| Fr::from(i * i + 1)).collect();
+        assert_eq!(crs.commit_evals_g1(&evals), crs.commit_poly_g1(&crs.interpolate(&evals).coeffs));
+
+        // pinned for a seeded CRS, so a change to the table encoding shows up here
+        use rand::SeedableRng;
+        let seeded: CRS = CRS::setup(rand_chacha::ChaCha20Rng::from_seed([7; 32]), 4);
+        let hex: String = seeded.fingerprint().iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "90c8405868e7295711d6cfb3deaabba0f662db6884d24a520639d8f4ef20b234");
+    }
+
+    #[test]
+    fn same_seed_is_compatible_and_different_seeds_are_not() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+        let setup = |seed: u8| CRS::<Bn254>::setup(ChaCha20Rng::from_seed([seed; 32]), 4);
+        let (a, b, c) = (setup(1), setup(1), setup(2));
+
+        assert!(a.is_compatible(&b));
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert!(!a.is_compatible(&c));
+        assert_ne!(a.fingerprint(), c.fingerprint());
+
+        let larger = CRS::<Bn254>::setup(ChaCha20Rng::from_seed([1; 32]), 8);
+        assert!(!a.is_compatible(&larger));
+    }
+
+    #[test]
+    fn coset_domain_interpolation_round_trips() {
+        use ark_poly::Polynomial;
+        let h = Fr::from(7u64);
+        let coset = GeneralEvaluationDomain::<Fr>::new_coset(4, h).unwrap();
+        let crs = CRS::<Bn254>::setup_with_domain(rand::rng(), coset);
+
+        let evals: Vec<Fr> = [3u64, 1, 4, 1].into_iter().map(Fr::from).collect();
+        let p = crs.interpolate(&evals);
+        for (x, e) in crs.domain_points().iter().zip(&evals) {
+            assert_eq!(p.evaluate(x), *e);
+        }
+        // the points really are the coset h·⟨ω⟩, where Z(X) = X^4 - h^4 vanishes
+        let z = DensePolynomial::from_coefficients_vec(crs.vanishing_coeffs.clone());
+        assert_eq!(crs.domain_points()[0], h);
+        assert!(crs.domain_points().iter().all(|x| z.evaluate(x).is_zero()));
+        assert_eq!(crs.vanishing_coeffs[0], -h.pow([4]));
+    }
+
+    #[test]
+    fn commit_shifted_matches_the_padded_commitment() {
+        use crate::helpers::mul_by_xk;
+        use rand::{Rng, SeedableRng};
+        let crs: CRS = CRS::setup(rand_chacha::ChaCha20Rng::from_seed([5; 32]), 8);
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed([6; 32]);
+        for deg in [0, 1, 4, 7] {
+            let coeffs: Vec<Fr> = (0..=deg).map(|_| Fr::from(rng.random::<u128>())).collect();
+            let b = DensePolynomial::from_coefficients_vec(coeffs);
+            for shift in [0, 1, crs.N - deg] {
+                assert_eq!(
+                    crs.commit_shifted(b.coeffs(), shift),
+                    crs.commit_poly_g1(mul_by_xk(&b, shift).coeffs()),
+                    "deg {} shift {}",
+                    deg,
+                    shift
+                );
+            }
+        }
+        // trailing zeros don't count towards the degree bound
+        let padded = [Fr::from(3u64), Fr::from(0u64), Fr::from(0u64)];
+        let trimmed = DensePolynomial::from_coefficients_slice(&padded[..1]);
+        assert_eq!(crs.commit_shifted(&padded, crs.N), crs.commit_poly_g1(mul_by_xk(&trimmed, crs.N).coeffs()));
+    }
+
+    #[test]
+    fn single_point_domain_works_end_to_end() {
+        use crate::iip::{iip_digest, iip_prove, iip_verify};
+        use crate::nonzero::{nonzero_prove, nonzero_verify};
+        let crs: CRS = CRS::setup(rand::rng(), 1);
+        assert_eq!(crs.n, 1);
+        assert_eq!(crs.n_inv, Fr::from(1u64));
+
+        let s = [Fr::from(1u64)];
+        let w = [Fr::from(5u64)];
+        let pi = iip_prove(&crs, &s, &w);
+        assert!(iip_verify(&iip_digest(&crs, &s), &pi));
+        assert_eq!(pi.v_g1, crs.g1_pows[0] * Fr::from(5u64));
+        assert!(nonzero_verify(&crs, &nonzero_prove(&crs, &s, 0), 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "CRS::setup: domain size n must be at least 1")]
+    fn empty_domain_is_rejected() {
+        let _: CRS = CRS::setup(rand::rng(), 0);
+    }
+
+    #[test]
+    fn hiding_commitments_differ_but_open() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let f: Vec<Fr> = [2u64, 0, 7].into_iter().map(Fr::from).collect();
+        let (r1, r2) = (Fr::from(11u64), Fr::from(13u64));
+        let (c1, c2) = (crs.commit_poly_g1_hiding(&f, r1), crs.commit_poly_g1_hiding(&f, r2));
+        assert_ne!(c1, c2);
+        assert_ne!(c1, crs.commit_poly_g1(&f));
+        assert!(crs.open_hiding_g1(&c1, &f, r1));
+        assert!(crs.open_hiding_g1(&c2, &f, r2));
+
+        assert!(!crs.open_hiding_g1(&c1, &f, r2));
+        assert!(!crs.open_hiding_g1(&c1, &[Fr::from(2u64), Fr::from(1u64)], r1));
+        // r = 0 is the plain commitment
+        assert_eq!(crs.commit_poly_g1_hiding(&f, Fr::from(0u64)), crs.commit_poly_g1(&f));
+    }
+
+    #[test]
+    fn domain_points_match_the_domain_elements() {
+        for n in [1, 2, 4, 16] {
+            let crs: CRS = CRS::setup(rand::rng(), n);
+            assert_eq!(crs.domain_points().len(), n);
+            for i in 0..n {
+                assert_eq!(crs.domain_points()[i], crs.domain.element(i), "n={} i={}", n, i);
+            }
+        }
+    }
+
+    #[test]
+    fn cached_vanishing_commitments_match_fresh_ones() {
+        use crate::iip::{iip_digest, iip_digest_g2};
+        use crate::mul_snark::bench_setup;
+        let (crs, dg, _) = bench_setup();
+        assert_eq!(crs.Z_tau_1, crs.commit_poly_g1(&crs.vanishing_coeffs));
+        assert_eq!(crs.Z_tau_2, crs.commit_poly_g2(&crs.vanishing_coeffs));
+
+        let fresh = crs.commit_poly_g2(&crs.vanishing_coeffs);
+        assert_eq!(iip_digest(&crs, &vec![Fr::from(1u64); crs.n]).Z_tau_2, fresh);
+        for d in [&dg.lv.iip_x, &dg.lv.iip_y, &dg.lv.iip_z] {
+            assert_eq!(d.Z_tau_2, fresh);
+        }
+        assert_eq!(iip_digest_g2(&crs, &vec![Fr::from(1u64); crs.n], "dual").Z_tau_1, crs.commit_poly_g1(&crs.vanishing_coeffs));
+    }
+
+    #[test]
+    fn commit_poly_both_matches_separate_commitments() {
+        let crs: CRS = CRS::setup(rand::rng(), 8);
+        for coeffs in [vec![], vec![Fr::from(0u64); 3], (1..=crs.N as u64 + 1).map(Fr::from).collect::<Vec<_>>()] {
+            let (g1, g2) = crs.commit_poly_both(&coeffs);
+            assert_eq!(g1, crs.commit_poly_g1(&coeffs));
+            assert_eq!(g2, crs.commit_poly_g2(&coeffs));
+        }
+    }
+
+    #[test]
+    fn chunked_commitment_matches_a_single_msm() {
+        use rand::{Rng, SeedableRng};
+        let domain = domain(4);
+        let crs: CRS = CRS::setup_with_degree(rand_chacha::ChaCha20Rng::from_seed([8; 32]), domain, 2048);
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed([9; 32]);
+        let coeffs: Vec<Fr> = (0..=2048).map(|_| Fr::from(rng.random::<u128>())).collect();
+        let full = crs.commit_poly_g1(&coeffs);
+        assert_eq!(crs.commit_poly_g1_chunked(&coeffs, 256), full);
+        assert_eq!(crs.commit_poly_g1_chunked(&coeffs, 1000), full);
+        assert_eq!(crs.commit_poly_g1_chunked(&coeffs, 4096), full);
+    }
+
+    #[test]
+    fn kzg_opens_at_an_off_domain_point() {
+        use ark_poly::Polynomial;
+        use rand::Rng;
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let vk = crs.verifier_key();
+        let w: Vec<Fr> = [12u64, 17, 204, 1].into_iter().map(Fr::from).collect();
+        let b = crs.interpolate(&w);
+        let c = crs.commit_poly_g1(b.coeffs());
+
+        let z = Fr::from(rand::rng().random::<u128>());
+        assert!(!crs.domain_points().contains(&z));
+        let (value, proof) = kzg_open(&crs, &w, z);
+        assert_eq!(value, b.evaluate(&z));
+        assert!(kzg_verify(&vk, c, z, value, proof));
+        assert!(!kzg_verify(&vk, c, z, value + Fr::from(1u64), proof));
+        assert!(!kzg_verify(&vk, c, z + Fr::from(1u64), value, proof));
+
+        // at a domain point the opening is the slot value
+        let (v2, p2) = kzg_open(&crs, &w, crs.domain_points()[2]);
+        assert_eq!(v2, w[2]);
+        assert!(kzg_verify(&vk, c, crs.domain_points()[2], v2, p2));
+    }
+
+    #[test]
+    fn batched_interpolation_matches_one_at_a_time() {
+        use crate::iip::Selector;
+        let crs: CRS = CRS::setup(rand::rng(), 8);
+        let mut evals: Vec<Vec<Fr>> = (0..8).map(|i| Selector::one_hot::<Fr>(8, i)).collect();
+        evals.push((10..18u64).map(Fr::from).collect());
+        evals.push(vec![Fr::from(0u64); 8]);
+
+        let batch = crs.interpolate_batch(&evals);
+        assert_eq!(batch.len(), evals.len());
+        for (p, e) in batch.iter().zip(&evals) {
+            assert_eq!(*p, crs.interpolate(e));
+        }
+        assert!(crs.interpolate_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn streamed_commitment_matches_the_slice_one() {
+        use rand::{Rng, SeedableRng};
+        let domain = domain(4);
+        let crs: CRS = CRS::setup_with_degree(rand_chacha::ChaCha20Rng::from_seed([10; 32]), domain, 5000);
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed([11; 32]);
+        // lengths around the internal 4096 chunk boundary
+        for len in [0, 1, 7, 4095, 4096, 4097, 5001] {
+            let coeffs: Vec<Fr> = (0..len).map(|_| Fr::from(rng.random::<u128>())).collect();
+            assert_eq!(
+                crs.commit_poly_g1_iter(coeffs.iter().copied()),
+                crs.commit_poly_g1(&coeffs),
+                "len {}",
+                len
+            );
+        }
+        // a lazily generated iterator, never collected
+        let lazy = (1..=100u64).map(Fr::from);
+        let collected: Vec<Fr> = (1..=100u64).map(Fr::from).collect();
+        assert_eq!(crs.commit_poly_g1_iter(lazy), crs.commit_poly_g1(&collected));
     }
 }
@@ -0,0 +1,159 @@
+//src/transcript.rs
+use ark_bn254::{Fq12, Fr, G1Projective as G1, G2Projective as G2};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+
+/// 256-bit hash a `Transcript` (and so the WE key/AAD derivation) runs on.
+/// `NAME` is absorbed into the WE context, so a ciphertext made under one
+/// hash never opens under another.
+pub trait CryptoHash: Clone {
+    const NAME: &'static [u8];
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize_32(self) -> [u8; 32];
+}
+
+impl CryptoHash for Sha256 {
+    const NAME: &'static [u8] = b"sha256";
+    fn new() -> Self {
+        Digest::new()
+    }
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(self, data);
+    }
+    fn finalize_32(self) -> [u8; 32] {
+        Digest::finalize(self).into()
+    }
+}
+
+impl CryptoHash for Sha3_256 {
+    const NAME: &'static [u8] = b"sha3-256";
+    fn new() -> Self {
+        Digest::new()
+    }
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(self, data);
+    }
+    fn finalize_32(self) -> [u8; 32] {
+        Digest::finalize(self).into()
+    }
+}
+
+/// Fiat–Shamir transcript over `H` (SHA-256 unless stated otherwise).
+///
+/// Every append is framed as (label length, label, data length, data), and
+/// group/field elements are fed in their canonical compressed encoding, so two
+/// transcripts agree iff they saw the same labelled values in the same order.
+#[derive(Clone)]
+pub struct Transcript<H: CryptoHash = Sha256> {
+    hasher: H,
+}
+
+impl Transcript {
+    /// Start a SHA-256 transcript under a protocol-level domain separator.
+    pub fn new(domain: &[u8]) -> Self {
+        Self::new_with_hash(domain)
+    }
+}
+
+impl<H: CryptoHash> Transcript<H> {
+    /// `new` over a caller-chosen hash.
+    pub fn new_with_hash(domain: &[u8]) -> Self {
+        let mut t = Transcript { hasher: H::new() };
+        t.append_bytes(b"dom-sep", domain);
+        t
+    }
+
+    pub fn append_bytes(&mut self, label: &[u8], bytes: &[u8]) {
+        self.hasher.update(&(label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update(&(bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], v: u64) {
+        self.append_bytes(label, &v.to_le_bytes());
+    }
+
+    /// Append any canonically serializable value (a group or field element of
+    /// a curve other than BN254, say).
+    pub fn append_serializable<T: CanonicalSerialize>(&mut self, label: &[u8], x: &T) {
+        let mut bytes = Vec::new();
+        x.serialize_compressed(&mut bytes).unwrap();
+        self.append_bytes(label, &bytes);
+    }
+
+    pub fn append_g1(&mut self, label: &[u8], g: &G1) {
+        self.append_serializable(label, g);
+    }
+
+    pub fn append_g2(&mut self, label: &[u8], g: &G2) {
+        self.append_serializable(label, g);
+    }
+
+    pub fn append_gt(&mut self, label: &[u8], gt: &Fq12) {
+        self.append_serializable(label, gt);
+    }
+
+    pub fn append_fr(&mut self, label: &[u8], x: &Fr) {
+        self.append_serializable(label, x);
+    }
+
+    /// 32 challenge bytes bound to everything appended so far. The challenge is
+    /// absorbed back, so consecutive challenges differ.
+    pub fn challenge_bytes(&mut self, label: &[u8]) -> [u8; 32] {
+        self.append_bytes(b"challenge", label);
+        let out = self.hasher.clone().finalize_32();
+        self.append_bytes(b"challenge-out", &out);
+        out
+    }
+
+    pub fn challenge_fr(&mut self, label: &[u8]) -> Fr {
+        self.challenge_scalar(label)
+    }
+
+    /// `challenge_fr` over an arbitrary prime field.
+    pub fn challenge_scalar<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        F::from_le_bytes_mod_order(&self.challenge_bytes(label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::PrimeGroup;
+
+    fn run(order: &[u8]) -> Fr {
+        let mut t = Transcript::new(b"test");
+        for &k in order {
+            match k {
+                0 => t.append_g1(b"g1", &G1::generator()),
+                1 => t.append_g2(b"g2", &G2::generator()),
+                _ => t.append_fr(b"fr", &Fr::from(7u64)),
+            }
+        }
+        t.challenge_fr(b"c")
+    }
+
+    #[test]
+    fn same_appends_same_challenge_reordered_differs() {
+        assert_eq!(run(&[0, 1, 2]), run(&[0, 1, 2]));
+        assert_ne!(run(&[0, 1, 2]), run(&[1, 0, 2]));
+        assert_ne!(run(&[0, 1, 2]), run(&[0, 1]));
+
+        // labels and the domain separator are bound too
+        let mut a = Transcript::new(b"test");
+        let mut b = Transcript::new(b"test");
+        let mut c = Transcript::new(b"other");
+        a.append_bytes(b"x", b"1");
+        b.append_bytes(b"y", b"1");
+        c.append_bytes(b"x", b"1");
+        let ca = a.challenge_bytes(b"c");
+        assert_ne!(ca, b.challenge_bytes(b"c"));
+        assert_ne!(ca, c.challenge_bytes(b"c"));
+        // consecutive challenges differ
+        assert_ne!(ca, a.challenge_bytes(b"c"));
+    }
+}
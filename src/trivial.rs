@@ -0,0 +1,187 @@
+// src/trivial.rs
+//
+// A trivially-true relation on the LV system: only the IIP, NonZero and
+// MaxDeg rows are kept, so the linear system and the WE plumbing can be
+// exercised without the Mul arithmetic. Testing and debugging only.
+
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::{One, Zero};
+use ark_poly::DenseUVPolynomial;
+
+use crate::iip::{Selector, iip_digest_tagged, iip_prove_with_commit};
+use crate::maxdeg::maxdeg_prove;
+use crate::nonzero::nonzero_prove;
+use crate::scs::CRS;
+use crate::verifier::{LVDigest, LVProof, LVShape, build_lv_coords, recover_sb_via_linear_check};
+
+/// Rows of `LVDigest::linear_shape` that do not involve the Mul gadget or
+/// the instance: the three IIP rows, NonZero and MaxDeg.
+pub const TRIVIAL_ROWS: [usize; 5] = [0, 1, 2, 3, 6];
+
+/// Columns no trivial row uses: the Mul commitments (c10-c13), the C–z
+/// binding (c14, c15) and the x/y openings (c18, c19). Their header
+/// exponents are zero, so a header ignores them; `trivial_verify` requires
+/// them to hold what `trivial_prove` puts there (identity Mul commitments,
+/// iip_x and iip_y opening the same v as iip_z) rather than anything at all.
+pub const TRIVIAL_FREE_COLS: [usize; 8] = [10, 11, 12, 13, 14, 15, 18, 19];
+
+/// Digest for "w has a 1 in its last slot": an IIP over the selector e_0,
+/// NonZero on the last slot and MaxDeg on B(X). Any such w satisfies it, so
+/// a header made for it (`trivial_public_linear_params`) protects nothing;
+/// its context differs from every Mul digest's, so it opens no Mul header.
+#[derive(Clone)]
+pub struct TrivialDigest<E: Pairing = Bn254> {
+    pub lv: LVDigest<E>,
+    pub s: Vec<E::ScalarField>,
+}
+
+impl<E: Pairing> TrivialDigest<E> {
+    pub fn setup(crs: &CRS<E>) -> Self {
+        let s = Selector::one_hot(crs.n, 0);
+        let iip = |tag| iip_digest_tagged(crs, &s, tag);
+        let d_bound = crs.n - 1;
+        let one = E::ScalarField::one();
+        // Unused by the trivial rows; LVDigest::new still wants the Mul
+        // vanishing polynomial X - 1 and a z0
+        let mul_z_tau_2 = crs.commit_poly_g2(&[-one, one]);
+        let lv = LVDigest::new(
+            crs,
+            iip("iip_x"),
+            iip("iip_y"),
+            iip("iip_z"),
+            crs.n - 1,
+            mul_z_tau_2,
+            E::ScalarField::zero(),
+            d_bound,
+            crs._g1_tau_pow(crs.N - d_bound),
+        )
+        .expect("TrivialDigest::setup: inconsistent LV digest");
+        TrivialDigest { lv, s }
+    }
+
+    /// `lv.linear_shape()` restricted to `TRIVIAL_ROWS`. The Mul, binding
+    /// and instance columns appear in no row, so their header exponents are
+    /// zero and their proof elements are unconstrained.
+    pub fn linear_shape(&self) -> LVShape<E> {
+        let full = self.lv.linear_shape();
+        let mut perm = TRIVIAL_ROWS.to_vec();
+        perm.extend((0..full.rows).filter(|i| !TRIVIAL_ROWS.contains(i)));
+        let mut shape = full.permute_rows(&perm);
+        shape.rows = TRIVIAL_ROWS.len();
+        shape
+    }
+}
+
+/// Proof for `TrivialDigest`: w must have one entry per domain slot and a 1
+/// in its last slot. The Mul commitments are the identity and iip_x/iip_y
+/// repeat iip_z, so the result is an ordinary `LVProof` that the WE layer
+/// accepts as is; it is not a valid Mul proof.
+#[allow(non_snake_case)]
+pub fn trivial_prove<E: Pairing>(crs: &CRS<E>, dg: &TrivialDigest<E>, w: &[E::ScalarField]) -> LVProof<E> {
+    assert_eq!(w.len(), crs.n, "trivial_prove: witness has {} slots, CRS n={}", w.len(), crs.n);
+    assert!(w[dg.lv.one_idx].is_one(), "trivial_prove: w[{}] must be 1", dg.lv.one_idx);
+
+    let B = crs.interpolate(w);
+    let w_tau_2 = crs.commit_poly_g2(B.coeffs());
+    let iip = iip_prove_with_commit(crs, &dg.s, w, &B, w_tau_2);
+    let w_hat_tau_1 = maxdeg_prove(crs, B.coeffs(), dg.lv.d_bound).expect("trivial_prove: deg B(X) <= n-1");
+
+    LVProof {
+        iip_x: iip.clone(),
+        iip_y: iip.clone(),
+        iip_z: iip,
+        nz: nonzero_prove(crs, w, dg.lv.one_idx),
+        w: w.to_vec(),
+        p_tau_1: E::G1::zero(),
+        h_tau_1: E::G1::zero(),
+        a_tau_1: E::G1::zero(),
+        b_tau_1: E::G1::zero(),
+        c_tau_1: E::G1::zero(),
+        w_hat_tau_1,
+    }
+}
+
+/// The linear check over the trivial shape, plus the `TRIVIAL_FREE_COLS`
+/// elements being what `trivial_prove` makes; none of `lv_verify`'s Mul-
+/// specific prechecks apply.
+pub fn trivial_verify<E: Pairing>(crs: &CRS<E>, dg: &TrivialDigest<E>, pi: &LVProof<E>) -> bool {
+    let mul_free = [pi.p_tau_1, pi.h_tau_1, pi.a_tau_1, pi.b_tau_1, pi.c_tau_1].iter().all(|g| g.is_zero());
+    if !mul_free || pi.iip_x.v_g1 != pi.iip_z.v_g1 || pi.iip_y.v_g1 != pi.iip_z.v_g1 {
+        return false;
+    }
+    match build_lv_coords(&crs.verifier_key(), &dg.lv, &pi.as_refs()) {
+        Some(coords) => recover_sb_via_linear_check(&dg.linear_shape(), &coords.0),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::MulDigest;
+    use crate::we::{
+        DecryptError, KeyDerivError, aead_encrypt, decrypt_with_lv_header, lv_make_header,
+        lv_public_linear_params, trivial_public_linear_params, DEFAULT_TAG_LEN,
+    };
+    use ark_bn254::Fr;
+
+    fn w4(x: u64, y: u64, z: u64) -> Vec<Fr> {
+        vec![Fr::from(x), Fr::from(y), Fr::from(z), Fr::from(1u64)]
+    }
+
+    #[test]
+    fn trivial_proof_verifies_and_opens_its_header() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let dg = TrivialDigest::setup(&crs);
+        let pi = trivial_prove(&crs, &dg, &w4(2, 3, 7));
+        assert!(trivial_verify(&crs, &dg, &pi));
+        assert_eq!(dg.lv.iip_x.gadget_tag, "iip_x");
+        assert_eq!(dg.lv.iip_y.gadget_tag, "iip_y");
+
+        let mut rng = rand::rng();
+        let params = trivial_public_linear_params(&crs, &dg).unwrap();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rng);
+        let mut buf = b"plumbing".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut buf);
+        let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [1; 12], &mut buf, &tag).unwrap();
+        assert_eq!(pt, b"plumbing");
+    }
+
+    #[test]
+    fn free_columns_are_enforced() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let dg = TrivialDigest::setup(&crs);
+        let pi = trivial_prove(&crs, &dg, &w4(2, 3, 7));
+
+        let mut bad = pi.clone();
+        bad.p_tau_1 = crs._g1_tau_pow(1);
+        assert!(!trivial_verify(&crs, &dg, &bad));
+        let mut bad = pi;
+        bad.iip_y.v_g1 += crs._g1_tau_pow(0);
+        assert!(!trivial_verify(&crs, &dg, &bad));
+    }
+
+    #[test]
+    fn trivial_proof_cannot_open_a_mul_header() {
+        let crs: CRS = CRS::setup(rand::rng(), 4);
+        let mut rng = rand::rng();
+        let mul = MulDigest::setup(&crs, Fr::from(6u64));
+        let mul_params = lv_public_linear_params(&crs, &mul.lv).unwrap();
+        let (hdr, key) = lv_make_header(&mul_params, &crs, &mut rng);
+        let mut ct = b"for 2*3 only".to_vec();
+        let tag = aead_encrypt(&crs, &mul_params, &hdr, &key, [2; 12], DEFAULT_TAG_LEN, &mut ct);
+
+        let dg = TrivialDigest::setup(&crs);
+        let triv_params = trivial_public_linear_params(&crs, &dg).unwrap();
+        // w = [2, 3, 7, 1] satisfies the trivial relation but not 2·3 = 6
+        let pi = trivial_prove(&crs, &dg, &w4(2, 3, 7));
+
+        // under the Mul parameters the derived key is wrong
+        let err = decrypt_with_lv_header(&crs, &mul.lv, &mul_params, &hdr, &pi, [2; 12], &mut ct.clone(), &tag);
+        assert!(matches!(err, Err(DecryptError::Aead(_))));
+        // under its own parameters the header context does not match
+        let err = decrypt_with_lv_header(&crs, &dg.lv, &triv_params, &hdr, &pi, [2; 12], &mut ct.clone(), &tag);
+        assert_eq!(err, Err(DecryptError::KeyDerivation(KeyDerivError::ContextMismatch)));
+    }
+}
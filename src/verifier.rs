@@ -1,88 +1,146 @@
 //src/verifier.rs
 use crate::iip::{IIPDigest, IIPProof, iip_verify};
-use crate::nonzero::{NonZeroProof, nonzero_verify};
-use crate::scs::CRS;
-use ark_bn254::{Bn254, Fq12, Fr, G1Projective as G1, G2Projective as G2};
-use ark_ec::pairing::Pairing;
-use ark_ec::PrimeGroup;
+use crate::nonzero::{NonZeroProof, nonzero_verify_vk};
+use crate::metrics::{Metrics, NoMetrics};
+use crate::scs::{CRS, VerifierKey};
+use ark_bn254::Bn254;
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{CurveGroup, PrimeGroup};
 use ark_ff::Field;
 use ark_ff::One;
+use ark_ff::Zero;
 use ark_ff::PrimeField;
-use ark_poly::EvaluationDomain;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use std::time::Instant;
 
 #[derive(Clone, Copy)]
 pub enum ColSide { ProofG1PublicG2, ProofG2PublicG1 }
 
 #[derive(Clone)]
-pub struct LVColMeta {
+pub struct LVColMeta<E: Pairing = Bn254> {
+    /// Domain-separation tag of the gadget owning this column; absorbed into
+    /// the KDF/AAD so numerically equal shapes from different gadgets differ.
+    pub tag: &'static str,
     pub side: ColSide,
-    pub g1_pub: Option<G1>,
-    pub g2_pub: Option<G2>,
+    pub g1_pub: Option<E::G1>,
+    pub g2_pub: Option<E::G2>,
 }
 
-pub enum ProofElem { G1(G1), G2(G2) }
+impl<E: Pairing> LVColMeta<E> {
+    /// The public base sits in the group opposite the proof element: a
+    /// `ProofG1PublicG2` column has only `g2_pub`, a `ProofG2PublicG1` only
+    /// `g1_pub`. Header construction relies on this.
+    pub fn validate(&self) -> bool {
+        match self.side {
+            ColSide::ProofG1PublicG2 => self.g1_pub.is_none() && self.g2_pub.is_some(),
+            ColSide::ProofG2PublicG1 => self.g1_pub.is_some() && self.g2_pub.is_none(),
+        }
+    }
+}
+
+pub enum ProofElem<E: Pairing = Bn254> { G1(E::G1), G2(E::G2) }
 
 #[derive(Clone)]
 #[allow(non_snake_case)]
-pub struct LVDigest {
-    pub iip_x: IIPDigest, 
-    pub iip_y: IIPDigest, 
-    pub iip_z: IIPDigest,
+pub struct LVDigest<E: Pairing = Bn254> {
+    pub iip_x: IIPDigest<E>, 
+    pub iip_y: IIPDigest<E>, 
+    pub iip_z: IIPDigest<E>,
     pub one_idx: usize,
-    pub mul_z_tau_2: G2,
-    pub instance_z: Fr,
+    pub mul_z_tau_2: E::G2,
+    pub instance_z: E::ScalarField,
+    // When set, replaces instance_z in Eq 7: [z0]_1 + r·[τ^N]_1 (see commit_instance)
+    pub instance_z_commitment: Option<E::G1>,
+    // When set, instance_z is absent and supplied per call to lv_verify_instance
+    pub instance_deferred: bool,
+    // Optional further public outputs [x0, y0], bound in Eq 10/11. This is
+    // the whole instance vector the fixed layout can carry: the Mul witness
+    // has three output slots, each with its own IIP column (c14, c18, c19),
+    // so there is no general `instance: Vec<_>` with a row per output.
+    pub instance_xy: [Option<E::ScalarField>; 2],
+    // When set, Eq 12 forces x = y (SquareGadget: A = B = w[in])
+    pub tie_xy: bool,
     // MaxDeg parameters for the IIP witness polynomial B(X)
     pub d_bound: usize,     // e.g. n-1
-    pub tau_N_minus_d_1: G1 // [τ^{N-d}]_1
+    pub tau_N_minus_d_1: E::G1 // [τ^{N-d}]_1
 }
 
-pub struct LVCoords(pub [Fq12; LV_NUM_COORDS]);
-pub(crate) fn build_lv_coords(crs: &CRS, dg: &LVDigest, pi: &LVProof) -> Option<LVCoords> {
+thread_local! {
+    static PAIRINGS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Per-thread count of the pairings done by the LV verification path: the
+/// gadget checks in `lv_prechecks` (IIP x/y in every build, IIP z and
+/// NonZero in debug builds), `build_lv_coords` and `linear_shape`.
+pub struct PairingCounter;
+
+impl PairingCounter {
+    pub fn reset() {
+        PAIRINGS.with(|c| c.set(0));
+    }
+
+    pub fn get() -> usize {
+        PAIRINGS.with(|c| c.get())
+    }
+}
+
+fn counted_pairing<E: Pairing>(a: E::G1, b: E::G2) -> E::TargetField {
+    gadget_pairing::<E>(a, b).0
+}
+
+/// `E::pairing`, seen by `PairingCounter`; the gadget verifiers use it so
+/// `lv_verify_counted` covers their checks too.
+pub(crate) fn gadget_pairing<E: Pairing>(a: impl Into<E::G1Prepared>, b: impl Into<E::G2Prepared>) -> PairingOutput<E> {
+    PAIRINGS.with(|c| c.set(c.get() + 1));
+    E::pairing(a, b)
+}
+
+pub struct LVCoords<E: Pairing = Bn254>(pub [E::TargetField; LV_NUM_COORDS]);
+pub(crate) fn build_lv_coords<E: Pairing>(vk: &VerifierKey<E>, dg: &LVDigest<E>, pi: &LVProofRefs<'_, E>) -> Option<LVCoords<E>> {
     // The NonZero and IIP commitments to B(τ) must match
     if pi.iip_z.w_tau_2 != pi.nz.w_tau_2 { return None; }
 
-    let g1 = <Bn254 as Pairing>::G1::generator();
-    let g2 = <Bn254 as Pairing>::G2::generator();
+    let g1 = E::G1::generator();
+    let g2 = E::G2::generator();
 
     // y*^{-1}
-    let y_inv = dg.iip_z.y_star.inverse().unwrap();
+    let y_inv = dg.iip_z.y_star.inverse()?;
 
     // d = D[one_idx]; [τ - d]_2
-    let d = crs.domain.element(dg.one_idx);
-    let tau_minus_d_2 = crs.g2_tau_pow(1) - g2.mul_bigint(d.into_bigint());
-
-    // Fill the coordinates (PairingOutputs turned into Fq12)
-    let c0 = <Bn254 as Pairing>::pairing(dg.iip_z.C,                pi.iip_z.w_tau_2).0;
-    let c1 = <Bn254 as Pairing>::pairing(pi.iip_z.v_g1.mul_bigint(y_inv.into_bigint()), g2).0;
-    let c2 = <Bn254 as Pairing>::pairing(pi.iip_z.QX_tau_1,         dg.iip_z.tau_2).0;
-    let c3 = <Bn254 as Pairing>::pairing(pi.iip_z.QZ_tau_1,         dg.iip_z.Z_tau_2).0;
-    let c4 = <Bn254 as Pairing>::pairing(pi.iip_z.QX_tau_1,         dg.iip_z.tau_N_minus_n_plus_2_2).0;
-    let c5 = <Bn254 as Pairing>::pairing(pi.iip_z.QX_hat_tau_1,     g2).0;
-    let c6 = <Bn254 as Pairing>::pairing(pi.iip_z.v_g1,             dg.iip_z.tau_N_2).0;
-    let c7 = <Bn254 as Pairing>::pairing(pi.iip_z.v_hat_tau_1,      g2).0;
-    let c8 = <Bn254 as Pairing>::pairing(g1,                      pi.nz.w_tau_2).0;
-    let c9 = <Bn254 as Pairing>::pairing(pi.nz.q0_tau_1,          tau_minus_d_2).0;
+    let tau_minus_d_2 = vk.tau_minus_d_2(dg.one_idx);
+
+    // Fill the coordinates (PairingOutputs turned into GT field elements)
+    let c0 = counted_pairing::<E>(dg.iip_z.C,                pi.iip_z.w_tau_2);
+    let c1 = counted_pairing::<E>(pi.iip_z.v_g1.mul_bigint(y_inv.into_bigint()), g2);
+    let c2 = counted_pairing::<E>(pi.iip_z.QX_tau_1,         dg.iip_z.tau_2);
+    let c3 = counted_pairing::<E>(pi.iip_z.QZ_tau_1,         dg.iip_z.Z_tau_2);
+    let c4 = counted_pairing::<E>(pi.iip_z.QX_tau_1,         dg.iip_z.tau_N_minus_n_plus_1_2);
+    let c5 = counted_pairing::<E>(pi.iip_z.QX_hat_tau_1,     g2);
+    let c6 = counted_pairing::<E>(pi.iip_z.v_g1,             dg.iip_z.tau_N_2);
+    let c7 = counted_pairing::<E>(pi.iip_z.v_hat_tau_1,      g2);
+    let c8 = counted_pairing::<E>(g1,                      pi.nz.w_tau_2);
+    let c9 = counted_pairing::<E>(pi.nz.q0_tau_1,          tau_minus_d_2);
     // Mul-gadget coordinates
-    let c10 = <Bn254 as Pairing>::pairing(pi.p_tau_1, g2).0;
-    let c11 = <Bn254 as Pairing>::pairing(pi.h_tau_1, dg.mul_z_tau_2).0;
-    let c12 = <Bn254 as Pairing>::pairing(pi.a_tau_1, g2).0; 
-    let c13 = <Bn254 as Pairing>::pairing(pi.b_tau_1, g2).0; 
+    let c10 = counted_pairing::<E>(*pi.p_tau_1, g2);
+    let c11 = counted_pairing::<E>(*pi.h_tau_1, dg.mul_z_tau_2);
+    let c12 = counted_pairing::<E>(*pi.a_tau_1, g2); 
+    let c13 = counted_pairing::<E>(*pi.b_tau_1, g2); 
 
     // C–z binding coordinates:
     // c14 = e(v_g1, g2), where v_g1 = z from IIP selector s = [0,0,1,0]
     // c15 = e(C(τ)_1, g2), where C(X) = z is the QAP output polynomial
-    let c14 = <Bn254 as Pairing>::pairing(pi.iip_z.v_g1, g2).0;
-    let c15 = <Bn254 as Pairing>::pairing(pi.c_tau_1, g2).0;
+    let c14 = counted_pairing::<E>(pi.iip_z.v_g1, g2);
+    let c15 = counted_pairing::<E>(*pi.c_tau_1, g2);
 
     // --- MaxDeg gadget coordinates ---
     // c16 = e([τ^{N-d}]_1, [B(τ)]_2) where B(X) is the IIP witness polynomial
-    let c16 = <Bn254 as Pairing>::pairing(dg.tau_N_minus_d_1, pi.iip_z.w_tau_2).0;
+    let c16 = counted_pairing::<E>(dg.tau_N_minus_d_1, pi.iip_z.w_tau_2);
     // c17 = e([X^{N-d} B(X)]_1, g2)
-    let c17 = <Bn254 as Pairing>::pairing(pi.w_hat_tau_1, g2).0;
+    let c17 = counted_pairing::<E>(*pi.w_hat_tau_1, g2);
 
     // A/B binding inside LV: x and y as G1 from IIP
-    let c18 = <Bn254 as Pairing>::pairing(pi.iip_x.v_g1, g2).0;
-    let c19 = <Bn254 as Pairing>::pairing(pi.iip_y.v_g1, g2).0;
+    let c18 = counted_pairing::<E>(pi.iip_x.v_g1, g2);
+    let c19 = counted_pairing::<E>(pi.iip_y.v_g1, g2);
 
     Some(LVCoords([
     c0,c1,c2,c3,c4,c5,c6,c7,c8,c9,
@@ -91,12 +149,12 @@ pub(crate) fn build_lv_coords(crs: &CRS, dg: &LVDigest, pi: &LVProof) -> Option<
 }
 
 /// Collect proof-side elements per column (G1 or G2), matching column order
-pub(crate) fn build_proof_side_elems(_crs: &CRS, dg: &LVDigest, pi: &LVProof)
-    -> Option<[ProofElem; LV_NUM_COORDS]>
+pub(crate) fn build_proof_side_elems<E: Pairing>(_crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProof<E>)
+    -> Option<[ProofElem<E>; LV_NUM_COORDS]>
 {
     if pi.iip_z.w_tau_2 != pi.nz.w_tau_2 { return None; }
 
-    let y_inv = dg.iip_z.y_star.inverse().unwrap();
+    let y_inv = dg.iip_z.y_star.inverse()?;
 
     Some([
         ProofElem::G2(pi.iip_z.w_tau_2),
@@ -127,19 +185,378 @@ pub(crate) fn build_proof_side_elems(_crs: &CRS, dg: &LVDigest, pi: &LVProof)
 }
 
 #[derive(Clone)]
-pub struct LVProof {
-    pub iip_x: IIPProof,
-    pub iip_y: IIPProof,
-    pub iip_z: IIPProof,
-    pub nz: NonZeroProof,
-    pub w: Vec<Fr>,
+pub struct LVProof<E: Pairing = Bn254> {
+    pub iip_x: IIPProof<E>,
+    pub iip_y: IIPProof<E>,
+    pub iip_z: IIPProof<E>,
+    pub nz: NonZeroProof<E>,
+    pub w: Vec<E::ScalarField>,
     // Mul-gadget commitments
-    pub p_tau_1: G1, // [P(τ)]_1
-    pub h_tau_1: G1, // [H(τ)]_1
-    pub a_tau_1: G1, // [A(τ)]_1
-    pub b_tau_1: G1, // [B(τ)]_1  (for A/B binding)
-    pub c_tau_1: G1, // [C(τ)]_1
-    pub w_hat_tau_1: G1,
+    pub p_tau_1: E::G1, // [P(τ)]_1
+    pub h_tau_1: E::G1, // [H(τ)]_1
+    pub a_tau_1: E::G1, // [A(τ)]_1
+    pub b_tau_1: E::G1, // [B(τ)]_1  (for A/B binding)
+    pub c_tau_1: E::G1, // [C(τ)]_1
+    pub w_hat_tau_1: E::G1,
+}
+
+// `Valid::check` is the on-curve + prime-order-subgroup test arkworks runs
+// when deserializing, available for G1 and G2 of every curve behind `Pairing`.
+fn in_subgroup<G: CurveGroup>(p: &G) -> bool {
+    p.into_affine().check().is_ok()
+}
+
+/// Borrowed view of an `LVProof`: verifiers that already hold the decoded
+/// elements (e.g. parsed out of a network message) check them in place with
+/// `lv_verify_refs` instead of cloning them, and the witness `Vec`, into an
+/// `LVProof` first.
+#[derive(Clone, Copy)]
+pub struct LVProofRefs<'a, E: Pairing = Bn254> {
+    pub iip_x: &'a IIPProof<E>,
+    pub iip_y: &'a IIPProof<E>,
+    pub iip_z: &'a IIPProof<E>,
+    pub nz: &'a NonZeroProof<E>,
+    pub w: &'a [E::ScalarField],
+    pub p_tau_1: &'a E::G1,
+    pub h_tau_1: &'a E::G1,
+    pub a_tau_1: &'a E::G1,
+    pub b_tau_1: &'a E::G1,
+    pub c_tau_1: &'a E::G1,
+    pub w_hat_tau_1: &'a E::G1,
+}
+
+impl<E: Pairing> LVProofRefs<'_, E> {
+    /// The pairing-free part of `lv_verify`, cheapest first: digest shape
+    /// (including `one_idx < n`, which `tau_minus_d_2` would otherwise wrap),
+    /// witness length, one shared [B(τ)]_2 across the IIP and NonZero
+    /// proofs, subgroup membership and `reject_degenerate`.
+    pub fn precheck(&self, dg: &LVDigest<E>) -> Result<(), PrecheckError> {
+        if dg.instance_deferred {
+            return Err(PrecheckError::InstanceDeferred);
+        }
+        for d in [&dg.iip_x, &dg.iip_y, &dg.iip_z] {
+            d.validate().map_err(PrecheckError::Digest)?;
+        }
+        if dg.one_idx >= dg.iip_z.n {
+            return Err(PrecheckError::OneIdxOutOfRange { one_idx: dg.one_idx, n: dg.iip_z.n });
+        }
+        if self.w.len() != 4 {
+            return Err(PrecheckError::WitnessLength { got: self.w.len() });
+        }
+        for (which, w_tau_2) in [
+            ("iip_x", &self.iip_x.w_tau_2),
+            ("iip_y", &self.iip_y.w_tau_2),
+            ("nonzero", &self.nz.w_tau_2),
+        ] {
+            if *w_tau_2 != self.iip_z.w_tau_2 {
+                return Err(PrecheckError::WitnessCommitmentMismatch { which });
+            }
+        }
+        if !self.check_subgroup() {
+            return Err(PrecheckError::NotInSubgroup);
+        }
+        if self.reject_degenerate() {
+            return Err(PrecheckError::Degenerate);
+        }
+        Ok(())
+    }
+
+    /// Every proof group element is on its curve and in the prime-order
+    /// subgroup. Trivial for BN254 G1 (cofactor 1) but not for G2.
+    pub fn check_subgroup(&self) -> bool {
+        let g1s = [
+            self.p_tau_1, self.h_tau_1, self.a_tau_1, self.b_tau_1,
+            self.c_tau_1, self.w_hat_tau_1, &self.nz.q0_tau_1,
+        ];
+        let iips = [self.iip_x, self.iip_y, self.iip_z];
+
+        g1s.iter().all(|p| in_subgroup(*p))
+            && in_subgroup(&self.nz.w_tau_2)
+            && iips.iter().all(|pi| {
+                in_subgroup(&pi.w_tau_2)
+                    && [&pi.v_g1, &pi.QZ_tau_1, &pi.QX_tau_1, &pi.QX_hat_tau_1, &pi.v_hat_tau_1]
+                        .iter()
+                        .all(|p| in_subgroup(*p))
+            })
+    }
+    /// True if the proof carries an identity element the Mul relation rules
+    /// out for its witness w = [x, y, z, 1]:
+    /// - any [B(τ)]_2 (IIP or NonZero): B interpolates the constant-1 slot,
+    ///   so it is never the zero polynomial;
+    /// - a_tau_1, b_tau_1, c_tau_1 or an IIP opening v_g1 whose slot in `w`
+    ///   is nonzero.
+    ///
+    /// Legitimately allowed: p_tau_1 and h_tau_1 (P = A·B - C is zero for a
+    /// valid witness), the quotient commitments, and a/b/c/v_g1 for a slot
+    /// that is zero (e.g. x = 0, z = 0).
+    pub fn reject_degenerate(&self) -> bool {
+        if self.w.len() != 4 {
+            return true;
+        }
+        let w_tau_2s = [&self.iip_x.w_tau_2, &self.iip_y.w_tau_2, &self.iip_z.w_tau_2, &self.nz.w_tau_2];
+        if w_tau_2s.iter().any(|p| p.is_zero()) {
+            return true;
+        }
+        [
+            (self.a_tau_1, &self.iip_x.v_g1, self.w[0]),
+            (self.b_tau_1, &self.iip_y.v_g1, self.w[1]),
+            (self.c_tau_1, &self.iip_z.v_g1, self.w[2]),
+        ]
+        .iter()
+        .any(|(com, v, slot)| !slot.is_zero() && (com.is_zero() || v.is_zero()))
+    }
+}
+
+impl<E: Pairing> LVProof<E> {
+    /// Borrow the proof's elements for `lv_verify_refs`.
+    pub fn as_refs(&self) -> LVProofRefs<'_, E> {
+        LVProofRefs {
+            iip_x: &self.iip_x,
+            iip_y: &self.iip_y,
+            iip_z: &self.iip_z,
+            nz: &self.nz,
+            w: &self.w,
+            p_tau_1: &self.p_tau_1,
+            h_tau_1: &self.h_tau_1,
+            a_tau_1: &self.a_tau_1,
+            b_tau_1: &self.b_tau_1,
+            c_tau_1: &self.c_tau_1,
+            w_hat_tau_1: &self.w_hat_tau_1,
+        }
+    }
+
+    /// See `LVProofRefs::precheck`.
+    pub fn precheck(&self, dg: &LVDigest<E>) -> Result<(), PrecheckError> {
+        self.as_refs().precheck(dg)
+    }
+
+    /// See `LVProofRefs::check_subgroup`.
+    pub fn check_subgroup(&self) -> bool {
+        self.as_refs().check_subgroup()
+    }
+
+    /// See `LVProofRefs::reject_degenerate`.
+    pub fn reject_degenerate(&self) -> bool {
+        self.as_refs().reject_degenerate()
+    }
+
+    /// The output z of the [x, y, z, 1] witness this proof carries, checked
+    /// against the commitments: `None` unless `w` has a z slot and [z]_1
+    /// equals both the IIP opening iip_z.v_g1 and [C(τ)]_1 (C(X) = z). For
+    /// a proof accepted by `lv_verify`, this is `Some(dg.instance_z)`.
+    pub fn claimed_z(&self) -> Option<E::ScalarField> {
+        let z = *self.w.get(2)?;
+        let z_g1 = E::G1::generator().mul_bigint(z.into_bigint());
+        (z_g1 == self.iip_z.v_g1 && z_g1 == self.c_tau_1).then_some(z)
+    }
+}
+
+/// Which pairing-free invariant `LVProof::precheck` found broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrecheckError {
+    /// The digest defers its instance; see `lv_verify_instance`.
+    InstanceDeferred,
+    /// A digest component is malformed.
+    Digest(DigestError),
+    /// The digest's constant-one slot lies outside the domain of size n.
+    OneIdxOutOfRange { one_idx: usize, n: usize },
+    /// The Mul circuit's witness has 4 slots [x, y, z, 1].
+    WitnessLength { got: usize },
+    /// The [B(τ)]_2 of `which` (an IIP proof or NonZero) differs from iip_z's;
+    /// all four gadgets must open the same witness.
+    WitnessCommitmentMismatch { which: &'static str },
+    /// A group element is off-curve or outside the prime-order subgroup.
+    NotInSubgroup,
+    /// See `LVProofRefs::reject_degenerate`.
+    Degenerate,
+}
+
+impl std::fmt::Display for PrecheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecheckError::InstanceDeferred => write!(f, "digest has no instance bound"),
+            PrecheckError::Digest(e) => write!(f, "malformed digest: {}", e),
+            PrecheckError::OneIdxOutOfRange { one_idx, n } => {
+                write!(f, "one_idx {} is outside the domain of size {}", one_idx, n)
+            }
+            PrecheckError::WitnessLength { got } => write!(f, "witness has {} slots, expected 4", got),
+            PrecheckError::WitnessCommitmentMismatch { which } => {
+                write!(f, "{} witness commitment differs from iip_z", which)
+            }
+            PrecheckError::NotInSubgroup => write!(f, "group element outside the prime-order subgroup"),
+            PrecheckError::Degenerate => write!(f, "identity commitment for a nonzero witness slot"),
+        }
+    }
+}
+
+impl std::error::Error for PrecheckError {}
+
+/// Why `LVProof::from_bytes` rejected its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The buffer ended before the proof did.
+    Truncated,
+    /// The witness length prefix exceeds the caller's bound.
+    TooLarge { len: u64, max: usize },
+    /// A group or field element failed to decode (or is off-subgroup).
+    BadElement,
+    /// Bytes remain after the proof.
+    TrailingBytes,
+}
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeserializeError::Truncated => write!(f, "proof bytes truncated"),
+            DeserializeError::TooLarge { len, max } => {
+                write!(f, "witness length {} exceeds bound {}", len, max)
+            }
+            DeserializeError::BadElement => write!(f, "invalid group or field element"),
+            DeserializeError::TrailingBytes => write!(f, "trailing bytes after proof"),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+fn read_elem<T: CanonicalDeserialize>(r: &mut &[u8]) -> Result<T, DeserializeError> {
+    if r.is_empty() {
+        return Err(DeserializeError::Truncated);
+    }
+    T::deserialize_compressed(r).map_err(|_| DeserializeError::BadElement)
+}
+
+impl<E: Pairing> LVProof<E> {
+    /// Compressed encoding: the three IIP proofs, the NonZero proof,
+    /// w as a u64 LE length followed by its elements, then the Mul/MaxDeg
+    /// commitments, all in struct order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for pi in [&self.iip_x, &self.iip_y, &self.iip_z] {
+            pi.w_tau_2.serialize_compressed(&mut out).unwrap();
+            for g in [&pi.v_g1, &pi.QZ_tau_1, &pi.QX_tau_1, &pi.QX_hat_tau_1, &pi.v_hat_tau_1] {
+                g.serialize_compressed(&mut out).unwrap();
+            }
+        }
+        self.nz.q0_tau_1.serialize_compressed(&mut out).unwrap();
+        self.nz.w_tau_2.serialize_compressed(&mut out).unwrap();
+        out.extend_from_slice(&(self.w.len() as u64).to_le_bytes());
+        for x in &self.w {
+            x.serialize_compressed(&mut out).unwrap();
+        }
+        for g in [
+            &self.p_tau_1, &self.h_tau_1, &self.a_tau_1,
+            &self.b_tau_1, &self.c_tau_1, &self.w_hat_tau_1,
+        ] {
+            g.serialize_compressed(&mut out).unwrap();
+        }
+        out
+    }
+
+    /// The proof's group elements, compressed, in `to_bytes` order with the
+    /// witness left out. Fixed length for a given curve; meant for absorbing
+    /// the proof into a Fiat–Shamir transcript (batch weights, blinding).
+    pub fn transcript_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for pi in [&self.iip_x, &self.iip_y, &self.iip_z] {
+            pi.w_tau_2.serialize_compressed(&mut out).unwrap();
+            for g in [&pi.v_g1, &pi.QZ_tau_1, &pi.QX_tau_1, &pi.QX_hat_tau_1, &pi.v_hat_tau_1] {
+                g.serialize_compressed(&mut out).unwrap();
+            }
+        }
+        self.nz.q0_tau_1.serialize_compressed(&mut out).unwrap();
+        self.nz.w_tau_2.serialize_compressed(&mut out).unwrap();
+        for g in [
+            &self.p_tau_1, &self.h_tau_1, &self.a_tau_1,
+            &self.b_tau_1, &self.c_tau_1, &self.w_hat_tau_1,
+        ] {
+            g.serialize_compressed(&mut out).unwrap();
+        }
+        out
+    }
+
+    /// Bytes each gadget contributes to `to_bytes`; the entries sum to
+    /// `to_bytes().len()`.
+    pub fn size_breakdown(&self) -> Vec<(&'static str, usize)> {
+        fn iip_size<E: Pairing>(pi: &IIPProof<E>) -> usize {
+            pi.w_tau_2.compressed_size()
+                + [&pi.v_g1, &pi.QZ_tau_1, &pi.QX_tau_1, &pi.QX_hat_tau_1, &pi.v_hat_tau_1]
+                    .iter()
+                    .map(|g| g.compressed_size())
+                    .sum::<usize>()
+        }
+        let mul = [&self.p_tau_1, &self.h_tau_1, &self.a_tau_1, &self.b_tau_1, &self.c_tau_1]
+            .iter()
+            .map(|g| g.compressed_size())
+            .sum();
+
+        vec![
+            ("iip_x", iip_size(&self.iip_x)),
+            ("iip_y", iip_size(&self.iip_y)),
+            ("iip_z", iip_size(&self.iip_z)),
+            ("nonzero", self.nz.q0_tau_1.compressed_size() + self.nz.w_tau_2.compressed_size()),
+            ("witness", 8 + self.w.iter().map(|x| x.compressed_size()).sum::<usize>()),
+            ("mul", mul),
+            ("maxdeg", self.w_hat_tau_1.compressed_size()),
+        ]
+    }
+
+    /// Inverse of `to_bytes`. The witness length prefix is checked against
+    /// `max_witness_len` (callers pass e.g. `crs.N`) and against the bytes
+    /// actually left before anything is allocated, so a forged prefix cannot
+    /// force a huge allocation.
+    #[allow(non_snake_case)]
+    pub fn from_bytes(bytes: &[u8], max_witness_len: usize) -> Result<Self, DeserializeError> {
+        let r = &mut &bytes[..];
+
+        let read_iip = |r: &mut &[u8]| -> Result<IIPProof<E>, DeserializeError> {
+            Ok(IIPProof {
+                w_tau_2: read_elem(r)?,
+                v_g1: read_elem(r)?,
+                QZ_tau_1: read_elem(r)?,
+                QX_tau_1: read_elem(r)?,
+                QX_hat_tau_1: read_elem(r)?,
+                v_hat_tau_1: read_elem(r)?,
+            })
+        };
+        let iip_x = read_iip(r)?;
+        let iip_y = read_iip(r)?;
+        let iip_z = read_iip(r)?;
+        let nz = NonZeroProof { q0_tau_1: read_elem(r)?, w_tau_2: read_elem(r)? };
+
+        let (len_bytes, rest) = r.split_first_chunk::<8>().ok_or(DeserializeError::Truncated)?;
+        *r = rest;
+        let len = u64::from_le_bytes(*len_bytes);
+        if len > max_witness_len as u64 {
+            return Err(DeserializeError::TooLarge { len, max: max_witness_len });
+        }
+        let len = len as usize;
+        if len.saturating_mul(E::ScalarField::zero().compressed_size()) > r.len() {
+            return Err(DeserializeError::Truncated);
+        }
+        let mut w = Vec::with_capacity(len);
+        for _ in 0..len {
+            w.push(read_elem(r)?);
+        }
+
+        let pi = LVProof {
+            iip_x,
+            iip_y,
+            iip_z,
+            nz,
+            w,
+            p_tau_1: read_elem(r)?,
+            h_tau_1: read_elem(r)?,
+            a_tau_1: read_elem(r)?,
+            b_tau_1: read_elem(r)?,
+            c_tau_1: read_elem(r)?,
+            w_hat_tau_1: read_elem(r)?,
+        };
+        if !r.is_empty() {
+            return Err(DeserializeError::TrailingBytes);
+        }
+        Ok(pi)
+    }
 }
 
 /// Number of GT-coordinates we use in A_LV · π = b_LV.
@@ -148,17 +565,354 @@ pub const LV_NUM_COORDS: usize = 20;
 /// A_LV and b_LV as described above.
 /// - a[i][j] ∈ {-1,0,1} describes exponent α_{i,j} on coordinate c_j in equation i.
 /// - b[i] ∈ GT is the RHS constant for equation i.
-pub struct LVShape {
+/// - row_tags[i] names equation i, so a row keeps its identity (and its b[i])
+///   if gadgets append rows in another order.
+pub struct LVShape<E: Pairing = Bn254> {
     pub rows: usize,
-    pub a: [[i8; LV_NUM_COORDS]; 10],
-    pub b: [Fq12; 10],
+    pub a: [[i8; LV_NUM_COORDS]; LV_MAX_ROWS],
+    pub b: [E::TargetField; LV_MAX_ROWS],
+    pub row_tags: [&'static str; LV_MAX_ROWS],
 }
 
-impl LVDigest {
-        pub fn linear_shape(&self, _crs: &CRS) -> LVShape {
-        let rows = 10;
+/// Row identifiers in the order `linear_shape` (and the packed encoding) uses.
+pub const LV_ROW_TAGS: [&str; LV_MAX_ROWS] = [
+    "iip_main", "iip_qx_deg", "iip_v_deg", "nonzero", "mul",
+    "cz_bind", "maxdeg", "instance", "ab_bind_x", "ab_bind_y",
+    "instance_x", "instance_y", "tie_xy",
+];
+
+/// Rows in the LV system: the ten gadget rows, one binding row per
+/// optional public output x, y and the optional x = y tie (each all-zero
+/// with b = 1 when unused).
+pub const LV_MAX_ROWS: usize = 13;
+
+impl<E: Pairing> LVShape<E> {
+    /// Pack A_LV at 2 bits per entry (00 = 0, 01 = 1, 10 = -1), row-major,
+    /// after a header of rows and cols as u32 LE. b_LV is not included.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + (self.rows * LV_NUM_COORDS).div_ceil(4));
+        out.extend_from_slice(&(self.rows as u32).to_le_bytes());
+        out.extend_from_slice(&(LV_NUM_COORDS as u32).to_le_bytes());
+
+        let mut byte = 0u8;
+        let mut k = 0;
+        for i in 0..self.rows {
+            for j in 0..LV_NUM_COORDS {
+                let code = match self.a[i][j] {
+                    0 => 0b00,
+                    1 => 0b01,
+                    -1 => 0b10,
+                    e => panic!("LVShape::pack: entry a[{}][{}] = {} not in {{-1,0,1}}", i, j, e),
+                };
+                byte |= code << (2 * (k % 4));
+                k += 1;
+                if k % 4 == 0 {
+                    out.push(byte);
+                    byte = 0;
+                }
+            }
+        }
+        if k % 4 != 0 {
+            out.push(byte);
+        }
+        out
+    }
+
+    /// Inverse of `pack`; the GT constants b_LV travel separately.
+    /// Returns `None` on a malformed header, length or entry code.
+    pub fn unpack(bytes: &[u8], b: [E::TargetField; LV_MAX_ROWS]) -> Option<LVShape<E>> {
+        let rows = u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?) as usize;
+        let cols = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?) as usize;
+        if rows > LV_MAX_ROWS || cols != LV_NUM_COORDS {
+            return None;
+        }
+        let body = &bytes[8..];
+        if body.len() != (rows * cols).div_ceil(4) {
+            return None;
+        }
+
+        let mut a = [[0i8; LV_NUM_COORDS]; LV_MAX_ROWS];
+        for k in 0..rows * cols {
+            a[k / cols][k % cols] = match (body[k / 4] >> (2 * (k % 4))) & 0b11 {
+                0b00 => 0,
+                0b01 => 1,
+                0b10 => -1,
+                _ => return None,
+            };
+        }
+        Some(LVShape { rows, a, b, row_tags: LV_ROW_TAGS })
+    }
+
+    /// α = A^T · r over the first `rows` rows: α_j = Σ_i a[i][j]·r_i, so a
+    /// +1 entry adds r_i to column j and a -1 entry subtracts it. This is
+    /// the exponent vector of the WE header columns.
+    pub fn transpose_apply(&self, r: &[E::ScalarField]) -> Vec<E::ScalarField> {
+        assert_eq!(r.len(), self.rows, "transpose_apply: need one r_i per row");
+        let mut alpha = vec![E::ScalarField::zero(); LV_NUM_COORDS];
+        for i in 0..self.rows {
+            let ri = r[i];
+            for j in 0..LV_NUM_COORDS {
+                match self.a[i][j] {
+                    1  => { alpha[j] += ri; }
+                    -1 => { alpha[j] -= ri; }
+                    _  => {}
+                }
+            }
+        }
+        alpha
+    }
+
+    /// Index of the row named `tag`.
+    pub fn row(&self, tag: &str) -> Option<usize> {
+        self.row_tags[..self.rows].iter().position(|t| *t == tag)
+    }
+
+    /// Reorder rows so that new row i is old row perm[i]; (tag, a, b) move
+    /// together. Panics unless `perm` is a permutation of 0..rows.
+    pub fn permute_rows(&self, perm: &[usize]) -> LVShape<E> {
+        let mut sorted = perm.to_vec();
+        sorted.sort_unstable();
+        assert!(
+            sorted.iter().copied().eq(0..self.rows),
+            "permute_rows: not a permutation of 0..{}",
+            self.rows
+        );
+        let mut out = LVShape { rows: self.rows, a: self.a, b: self.b, row_tags: self.row_tags };
+        for (i, &j) in perm.iter().enumerate() {
+            out.a[i] = self.a[j];
+            out.b[i] = self.b[j];
+            out.row_tags[i] = self.row_tags[j];
+        }
+        out
+    }
+}
 
-        let mut a = [[0i8; LV_NUM_COORDS]; 10];
+/// Why a digest is inconsistent with the CRS it is used with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DigestError {
+    /// An IIP digest (`which` = its gadget tag) was built for another CRS/domain.
+    IipMismatch { which: &'static str },
+    /// one_idx does not address a slot of the domain.
+    OneIdxOutOfRange { one_idx: usize, n: usize },
+    /// The MaxDeg bound exceeds the CRS degree.
+    DegreeBoundTooLarge { d_bound: usize, max_deg: usize },
+    /// tau_N_minus_d_1 is not [τ^{N-d}]_1.
+    BadMaxDegBase,
+    /// mul_z_tau_2 is not [τ - 1]_2, the Mul gate's vanishing polynomial.
+    BadMulVanishing,
+    /// An IIP digest has y* = 0, which has no inverse.
+    ZeroYStar { which: &'static str },
+    /// An IIP digest claims a domain larger than its CRS degree.
+    DomainTooLarge { which: &'static str, n: usize, max_deg: usize },
+    /// An IIP digest G2 base is the identity.
+    IdentityElement { which: &'static str, field: &'static str },
+    /// The digest defers its instance; bind one with `with_instance` first.
+    InstanceDeferred,
+    /// The verifier's CRS does not reproduce `what`, an element the digest
+    /// or `lv_verify` reads (different trapdoor, domain, or a smaller N).
+    VerifierCrsMismatch { what: &'static str },
+    /// Column `col` of `column_metadata` fails `LVColMeta::validate`.
+    MalformedColumn { col: usize },
+}
+
+impl std::fmt::Display for DigestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DigestError::IipMismatch { which } => {
+                write!(f, "IIP digest {} does not match the CRS", which)
+            }
+            DigestError::OneIdxOutOfRange { one_idx, n } => {
+                write!(f, "one_idx={} out of range for n={}", one_idx, n)
+            }
+            DigestError::DegreeBoundTooLarge { d_bound, max_deg } => {
+                write!(f, "d_bound={} exceeds CRS.N={}", d_bound, max_deg)
+            }
+            DigestError::BadMaxDegBase => write!(f, "tau_N_minus_d_1 != [τ^(N-d)]_1"),
+            DigestError::BadMulVanishing => write!(f, "mul_z_tau_2 != [τ - 1]_2"),
+            DigestError::ZeroYStar { which } => write!(f, "IIP digest {} has y_star = 0", which),
+            DigestError::DomainTooLarge { which, n, max_deg } => {
+                write!(f, "IIP digest {} has n={} > N={}", which, n, max_deg)
+            }
+            DigestError::IdentityElement { which, field } => {
+                write!(f, "IIP digest {} has identity {}", which, field)
+            }
+            DigestError::InstanceDeferred => write!(f, "digest has no instance bound"),
+            DigestError::MalformedColumn { col } => {
+                write!(f, "column {}: public base does not match its orientation", col)
+            }
+            DigestError::VerifierCrsMismatch { what } => {
+                write!(f, "verifier CRS does not match the digest's {}", what)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DigestError {}
+
+/// The CRS-derived elements of an IIP digest agree with `crs`.
+fn iip_matches_crs<E: Pairing>(d: &IIPDigest<E>, crs: &CRS<E>) -> bool {
+    d.n == crs.n
+        && d.N == crs.N
+        && d.tau_2 == crs.g2_tau_pow(1)
+        && d.tau_N_minus_n_plus_1_2 == crs.g2_tau_pow(crs.N - crs.n + 1)
+        && d.tau_N_2 == crs.g2_tau_pow(crs.N)
+        && d.Z_tau_2 == crs.Z_tau_2
+}
+
+impl<E: Pairing> LVDigest<E> {
+    /// Assemble an LVDigest from its parts, checking them against `crs`.
+    /// `MulDigest::setup` goes through here; other gadget assemblies and
+    /// interop tests can too.
+    #[allow(non_snake_case)]
+    pub fn new(
+        crs: &CRS<E>,
+        iip_x: IIPDigest<E>,
+        iip_y: IIPDigest<E>,
+        iip_z: IIPDigest<E>,
+        one_idx: usize,
+        mul_z_tau_2: E::G2,
+        instance_z: E::ScalarField,
+        d_bound: usize,
+        tau_N_minus_d_1: E::G1,
+    ) -> Result<Self, DigestError> {
+        for d in [&iip_x, &iip_y, &iip_z] {
+            d.validate()?;
+            if !iip_matches_crs(d, crs) {
+                return Err(DigestError::IipMismatch { which: d.gadget_tag });
+            }
+        }
+        if one_idx >= crs.n {
+            return Err(DigestError::OneIdxOutOfRange { one_idx, n: crs.n });
+        }
+        if d_bound > crs.N {
+            return Err(DigestError::DegreeBoundTooLarge { d_bound, max_deg: crs.N });
+        }
+        if tau_N_minus_d_1 != crs._g1_tau_pow(crs.N - d_bound) {
+            return Err(DigestError::BadMaxDegBase);
+        }
+        if mul_z_tau_2 != crs.commit_poly_g2(&[-E::ScalarField::one(), E::ScalarField::one()]) {
+            return Err(DigestError::BadMulVanishing);
+        }
+
+        Ok(LVDigest {
+            iip_x,
+            iip_y,
+            iip_z,
+            one_idx,
+            mul_z_tau_2,
+            instance_z,
+            instance_z_commitment: None,
+            instance_deferred: false,
+            instance_xy: [None, None],
+            tie_xy: false,
+            d_bound,
+            tau_N_minus_d_1,
+        })
+    }
+
+    /// `crs` can verify proofs against this digest: same domain and [τ]_2,
+    /// and, where N >= the digest's N, the same powers at the indices the
+    /// digest took from its own CRS. A verifier CRS of larger degree from
+    /// the same trapdoor passes; a different τ, domain or a smaller N does
+    /// not.
+    #[allow(non_snake_case)]
+    pub fn check_verifier_crs(&self, crs: &CRS<E>) -> Result<(), DigestError> {
+        let d = &self.iip_z;
+        let mismatch = |what| Err(DigestError::VerifierCrsMismatch { what });
+        // a coset domain of the same size shows up in [Z(τ)]_2 below
+        if crs.n != d.n {
+            return mismatch("domain size n");
+        }
+        if crs.N < d.N {
+            return mismatch("degree N");
+        }
+        if crs.g2_tau_pow(1) != d.tau_2 {
+            return mismatch("[τ]_2");
+        }
+        if crs.Z_tau_2 != d.Z_tau_2 {
+            return mismatch("[Z(τ)]_2");
+        }
+        if crs.g2_tau_pow(d.N - d.n + 1) != d.tau_N_minus_n_plus_1_2 {
+            return mismatch("[τ^(N-n+1)]_2");
+        }
+        if crs.g2_tau_pow(d.N) != d.tau_N_2 {
+            return mismatch("[τ^N]_2");
+        }
+        if self.d_bound > d.N || crs._g1_tau_pow(d.N - self.d_bound) != self.tau_N_minus_d_1 {
+            return mismatch("[τ^(N-d)]_1");
+        }
+        Ok(())
+    }
+
+    /// Make x and/or y public outputs alongside z: a proof then verifies
+    /// only if its x (resp. y) equals the given value. With z this covers
+    /// every output slot of the [x, y, z, 1] layout; circuits with more
+    /// public outputs need more IIP columns, which the fixed LV layout
+    /// does not have.
+    pub fn bind_outputs(mut self, x0: Option<E::ScalarField>, y0: Option<E::ScalarField>) -> Self {
+        self.instance_xy = [x0, y0];
+        self
+    }
+
+    /// Require x = y, so the Mul relation x·y = z becomes x^2 = z.
+    pub fn tie_inputs(mut self) -> Self {
+        self.tie_xy = true;
+        self
+    }
+
+    /// One digest for many instances: drops instance_z, so Eq 7's RHS is
+    /// rebuilt from the z0 passed to `lv_verify_instance`. `lv_verify` and
+    /// `linear_shape` (hence encryption) need a bound instance, see
+    /// `with_instance`.
+    pub fn defer_instance(&self) -> Self {
+        LVDigest {
+            instance_z: E::ScalarField::zero(),
+            instance_z_commitment: None,
+            instance_deferred: true,
+            ..self.clone()
+        }
+    }
+
+    /// This digest with the public output fixed to z0.
+    pub fn with_instance(&self, z0: E::ScalarField) -> Self {
+        LVDigest {
+            instance_z: z0,
+            instance_z_commitment: None,
+            instance_deferred: false,
+            ..self.clone()
+        }
+    }
+
+    /// Hide the instance behind a Pedersen-style commitment
+    /// [z0]_1 + r·[τ^N]_1: the returned digest no longer carries z0.
+    /// Only holders of `r` can build the linear shape, via `open_instance`.
+    pub fn commit_instance(&self, crs: &CRS<E>, r: E::ScalarField) -> Self {
+        let g1 = E::G1::generator();
+        let c = g1.mul_bigint(self.instance_z.into_bigint())
+            + crs._g1_tau_pow(crs.N).mul_bigint(r.into_bigint());
+        LVDigest {
+            instance_z: E::ScalarField::zero(),
+            instance_z_commitment: Some(c),
+            ..self.clone()
+        }
+    }
+
+    /// The encryptor's (or verifier's) view of a committed-instance digest:
+    /// strips r·[τ^N]_1 so the commitment becomes [z0]_1 and Eq 7 binds the
+    /// proof's z to it. A wrong `r` yields a shape no proof satisfies.
+    pub fn open_instance(&self, crs: &CRS<E>, r: E::ScalarField) -> Self {
+        let mut dg = self.clone();
+        if let Some(c) = dg.instance_z_commitment {
+            dg.instance_z_commitment = Some(c - crs._g1_tau_pow(crs.N).mul_bigint(r.into_bigint()));
+        }
+        dg
+    }
+
+        pub fn linear_shape(&self) -> LVShape<E> {
+        assert!(!self.instance_deferred, "linear_shape: instance deferred, call with_instance first");
+        let rows = LV_MAX_ROWS;
+
+        let mut a = [[0i8; LV_NUM_COORDS]; LV_MAX_ROWS];
 
         // Eq 0: c0 * c1^{-1} * c2^{-1} * c3^{-1} = 1
         a[0] = [ 1, -1, -1, -1,  0,  0,  0,  0,  0,  0,
@@ -201,123 +955,1046 @@ impl LVDigest {
                  0,  0,  0,  1,  0,  0,  0,  0,  0, -1];
 
 
-        let gt_one = Fq12::one();
-        let gt_const: Fq12 = <Bn254 as Pairing>::pairing(
-            <Bn254 as Pairing>::G1::generator(),
-            <Bn254 as Pairing>::G2::generator(),
-        ).0;
+        let gt_one = E::TargetField::one();
+        let gt_const: E::TargetField = counted_pairing::<E>(
+            E::G1::generator(),
+            E::G2::generator(),
+        );
 
-        let mut b = [gt_one.clone(); 10];
+        let mut b = [gt_one; LV_MAX_ROWS];
         b[3] = gt_const;
 
-        // Eq 7: z = z0 ⇒ c14 = e(z0·G1, G2)
-        let g1 = <Bn254 as Pairing>::G1::generator();
-        let g2 = <Bn254 as Pairing>::G2::generator();
-        let z0_g1 = g1.mul_bigint(self.instance_z.into_bigint());
-        b[7] = <Bn254 as Pairing>::pairing(z0_g1, g2).0;
+        // Eq 7: z = z0 ⇒ c14 = e(z0·G1, G2); an (opened) commitment
+        // supplies [z0]_1 directly
+        let g1 = E::G1::generator();
+        let g2 = E::G2::generator();
+        let z0_g1 = match self.instance_z_commitment {
+            Some(c) => c,
+            None => g1.mul_bigint(self.instance_z.into_bigint()),
+        };
+        b[7] = counted_pairing::<E>(z0_g1, g2);
+
+        // Eq 10/11: optional public outputs x = x0 (c18), y = y0 (c19)
+        for (row, col, v) in [(10, 18, self.instance_xy[0]), (11, 19, self.instance_xy[1])] {
+            if let Some(v) = v {
+                a[row][col] = 1;
+                b[row] = counted_pairing::<E>(g1.mul_bigint(v.into_bigint()), g2);
+            }
+        }
+
+        // Eq 12: c18 * c19^{-1} = 1   (x == y, optional)
+        if self.tie_xy {
+            a[12][18] = 1;
+            a[12][19] = -1;
+        }
         
-        LVShape { rows, a, b }
+        LVShape { rows, a, b, row_tags: LV_ROW_TAGS }
     }
 
 
-    /// Map each column to its public base and orientation
-    pub fn column_metadata(&self, crs: &CRS) -> [LVColMeta; LV_NUM_COORDS] {
-        let g1 = <Bn254 as Pairing>::G1::generator();
-        let g2 = <Bn254 as Pairing>::G2::generator();
-        let d = crs.domain.element(self.one_idx);
+    /// Map each column to its public base and orientation. Fails if
+    /// `one_idx` is outside the CRS domain.
+    pub fn column_metadata(&self, crs: &CRS<E>) -> Result<[LVColMeta<E>; LV_NUM_COORDS], DigestError> {
+        let g1 = E::G1::generator();
+        let g2 = E::G2::generator();
+        let d = *crs
+            .domain_points()
+            .get(self.one_idx)
+            .ok_or(DigestError::OneIdxOutOfRange { one_idx: self.one_idx, n: crs.n })?;
         let tau_minus_d_2 = crs.g2_tau_pow(1) - g2.mul_bigint(d.into_bigint());
 
-        [
+        Ok([
             // c0 = e(C, w_tau_2): proof is G2, public base is G1 (C)
-            LVColMeta { side: ColSide::ProofG2PublicG1, g1_pub: Some(self.iip_z.C), g2_pub: None },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG2PublicG1, g1_pub: Some(self.iip_z.C), g2_pub: None },
             // c1 = e(v_g1 * y_inv, g2): proof is G1, public base is g2
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c2 = e(QX_tau_1, tau_2): proof G1, public G2
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_2) },
             // c3 = e(QZ_tau_1, Z_tau_2): proof G1, public G2
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.Z_tau_2) },
-            // c4 = e(QX_tau_1, tau_{N-n+2,2})
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_N_minus_n_plus_2_2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.Z_tau_2) },
+            // c4 = e(QX_tau_1, tau_{N-n+1,2})
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_N_minus_n_plus_1_2) },
             // c5 = e(QX_hat_tau_1, g2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c6 = e(v_g1, tau_N_2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_N_2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.iip_z.tau_N_2) },
             // c7 = e(v_hat_tau_1, g2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: self.iip_z.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c8 = e(g1, w_tau_2): proof G2, public G1
-            LVColMeta { side: ColSide::ProofG2PublicG1, g1_pub: Some(g1), g2_pub: None },
+            LVColMeta { tag: "nonzero", side: ColSide::ProofG2PublicG1, g1_pub: Some(g1), g2_pub: None },
             // c9 = e(q0_tau_1, (tau - d)_2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(tau_minus_d_2) },
+            LVColMeta { tag: "nonzero", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(tau_minus_d_2) },
             // c10 = e(P_tau_1, g2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "mul", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c11 = e(H_tau_1, Z_tau_2)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.mul_z_tau_2) },
+            LVColMeta { tag: "mul", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(self.mul_z_tau_2) },
             // c12 = e(A_tau_1, g2) optional
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "mul", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c13 = e(B_tau_1, g2) optional
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "mul", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c14 = e(v_g1, g2)  (z from IIP)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "cz_bind", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
             // c15 = e(C_tau_1, g2)  (z from QAP C)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "cz_bind", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
 
             // c16 = e([τ^{N-d}]_1, [B(τ)]_2): proof G2, public G1
-            LVColMeta { side: ColSide::ProofG2PublicG1, g1_pub: Some(self.tau_N_minus_d_1), g2_pub: None },
+            LVColMeta { tag: "maxdeg", side: ColSide::ProofG2PublicG1, g1_pub: Some(self.tau_N_minus_d_1), g2_pub: None },
 
             // c17 = e([X^{N-d} B(X)]_1, g2): proof G1, public g2
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: "maxdeg", side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
 
             // c18 = e(v_x_g1, g2)  (x from IIP_x)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+            LVColMeta { tag: self.iip_x.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
 
             // c19 = e(v_y_g1, g2)  (y from IIP_y)
-            LVColMeta { side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
-        ]
+            LVColMeta { tag: self.iip_y.gadget_tag, side: ColSide::ProofG1PublicG2, g1_pub: None, g2_pub: Some(g2) },
+        ])
     }
 }
 
 
-pub fn recover_sb_via_linear_check(
-    shape: &LVShape,
-    coords: &[Fq12; LV_NUM_COORDS],
+pub fn recover_sb_via_linear_check<E: Pairing>(
+    shape: &LVShape<E>,
+    coords: &[E::TargetField; LV_NUM_COORDS],
 ) -> bool {
-    for i in 0..shape.rows {
-        let mut lhs = Fq12::one();
+    // Rows are checked as a set of tagged equations, each against its own
+    // b[i]; a repeated tag would let one equation stand in for another.
+    let tags = &shape.row_tags[..shape.rows];
+    if tags.iter().enumerate().any(|(i, t)| tags[..i].contains(t)) {
+        return false;
+    }
+
+    // Invert each coordinate used with exponent -1 once, up front. The
+    // Mul shape negates every column at most once, so this saves nothing
+    // there, but conjunction/batched shapes that reuse a column across rows
+    // pay one Fq12 inversion per column instead of one per entry.
+    let mut inv: [Option<E::TargetField>; LV_NUM_COORDS] = [None; LV_NUM_COORDS];
+    for j in 0..LV_NUM_COORDS {
+        if (0..shape.rows).any(|i| shape.a[i][j] == -1) {
+            match coords[j].inverse() {
+                Some(x) => inv[j] = Some(x),
+                None => return false,
+            }
+        }
+    }
+
+    let row_holds = |i: usize| {
+        let mut lhs = E::TargetField::one();
         for j in 0..LV_NUM_COORDS {
-            let e = shape.a[i][j];
-            if e == 0 { continue; }
-            if e == 1  { lhs *= &coords[j]; }
-            if e == -1 {
-                let inv = coords[j].inverse().unwrap();
-                lhs *= &inv;
+            match shape.a[i][j] {
+                1 => lhs *= &coords[j],
+                -1 => lhs *= inv[j].as_ref().unwrap(),
+                _ => {}
             }
         }
-        if lhs != shape.b[i] { return false; }
+        lhs == shape.b[i]
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..shape.rows).into_par_iter().all(row_holds)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..shape.rows).all(row_holds)
     }
-    true
 }
 
+/// What `lv_verify` reads from `crs` is only the `VerifierKey`: n, the
+/// domain and [τ]_2 (plus [τ - D[one_idx]]_2 derived from them). Every
+/// N-dependent base ([τ^{N-n+1}]_2, [τ^N]_2, [τ^{N-d}]_1, [Z(τ)]_2) comes
+/// from the digest, fixed by the CRS it was built under. So a digest and
+/// proof made under degree N1 verify against any CRS from the same τ and
+/// domain; `LVDigest::check_verifier_crs` confirms that for N2 >= N1.
 #[allow(non_snake_case)]
-pub fn lv_verify(crs: &CRS, dg: &LVDigest, pi: &LVProof) -> bool {
-    // Basic relation check on witness length.
-    if pi.w.len() != 4 {
+pub fn lv_verify<E: Pairing>(crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProof<E>) -> bool {
+    debug_assert!(
+        lv_layout_consistent(crs, dg, pi),
+        "lv_verify: column tables (metadata / coords / proof elements / shape) disagree"
+    );
+    lv_verify_with_vk(&crs.verifier_key(), dg, pi)
+}
+
+/// `lv_verify` for a digest from `defer_instance`: accepts iff the proof is
+/// valid for public output `instance`.
+pub fn lv_verify_instance<E: Pairing>(crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProof<E>, instance: E::ScalarField) -> bool {
+    lv_verify(crs, &dg.with_instance(instance), pi)
+}
+
+/// Cross-check the per-column tables against each other. Their lengths are
+/// fixed to LV_NUM_COORDS by type; what can still go wrong when a column is
+/// added is order or orientation, so for every column j this checks that
+/// pairing `build_proof_side_elems`' element with `column_metadata`'s public
+/// base gives `build_lv_coords`' c_j, and that some row of `linear_shape`
+/// uses c_j. Vacuously true for a proof rejected before coordinates exist.
+/// Costs LV_NUM_COORDS extra pairings, which `PairingCounter` does not see.
+pub fn lv_layout_consistent<E: Pairing>(crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProof<E>) -> bool {
+    if dg.one_idx >= crs.n || dg.iip_z.y_star.is_zero() || dg.instance_deferred {
+        return true;
+    }
+    let counted = PairingCounter::get();
+    let vk = crs.verifier_key();
+    let coords = build_lv_coords(&vk, dg, &pi.as_refs());
+    let elems = build_proof_side_elems(crs, dg, pi);
+    let shape = dg.linear_shape();
+    PAIRINGS.with(|c| c.set(counted));
+    let (Some(coords), Some(elems)) = (coords, elems) else {
+        return true;
+    };
+
+    match dg.column_metadata(crs) {
+        Ok(cols) => columns_agree(&cols, &elems, &coords.0, &shape),
+        Err(_) => true,
+    }
+}
+
+/// The per-column part of `lv_layout_consistent`: e(elems[j], base_j) is
+/// coords[j] with the orientation `cols[j]` declares, and `shape` uses every column.
+fn columns_agree<E: Pairing>(
+    cols: &[LVColMeta<E>; LV_NUM_COORDS],
+    elems: &[ProofElem<E>; LV_NUM_COORDS],
+    coords: &[E::TargetField; LV_NUM_COORDS],
+    shape: &LVShape<E>,
+) -> bool {
+    let pairs_up = (0..LV_NUM_COORDS).all(|j| {
+        let gt = match (cols[j].side, &elems[j], cols[j].g1_pub, cols[j].g2_pub) {
+            (ColSide::ProofG1PublicG2, ProofElem::G1(p), None, Some(q)) => E::pairing(*p, q),
+            (ColSide::ProofG2PublicG1, ProofElem::G2(q), Some(p), None) => E::pairing(p, *q),
+            _ => return false,
+        };
+        gt.0 == coords[j]
+    });
+    let all_used = (0..LV_NUM_COORDS).all(|j| (0..shape.rows).any(|i| shape.a[i][j] != 0));
+    pairs_up && all_used
+}
+
+/// `lv_verify` against a `VerifierKey`: same checks, no CRS power tables.
+pub fn lv_verify_with_vk<E: Pairing>(vk: &VerifierKey<E>, dg: &LVDigest<E>, pi: &LVProof<E>) -> bool {
+    lv_verify_refs_with_vk(vk, dg, &pi.as_refs())
+}
+
+/// `lv_verify` on borrowed proof elements; same result as `lv_verify` on the
+/// `LVProof` they came from.
+pub fn lv_verify_refs<E: Pairing>(crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProofRefs<'_, E>) -> bool {
+    lv_verify_refs_with_vk(&crs.verifier_key(), dg, pi)
+}
+
+/// `lv_verify_refs` against a `VerifierKey`.
+pub fn lv_verify_refs_with_vk<E: Pairing>(vk: &VerifierKey<E>, dg: &LVDigest<E>, pi: &LVProofRefs<'_, E>) -> bool {
+    lv_verify_staged(vk, dg, pi, &mut NoMetrics)
+}
+
+/// `lv_verify` reporting each stage's duration to `m`.
+pub fn lv_verify_with_metrics<E: Pairing, M: Metrics + ?Sized>(
+    crs: &CRS<E>,
+    dg: &LVDigest<E>,
+    pi: &LVProof<E>,
+    m: &mut M,
+) -> bool {
+    lv_verify_staged(&crs.verifier_key(), dg, &pi.as_refs(), m)
+}
+
+fn lv_verify_staged<E: Pairing, M: Metrics + ?Sized>(
+    vk: &VerifierKey<E>,
+    dg: &LVDigest<E>,
+    pi: &LVProofRefs<'_, E>,
+    m: &mut M,
+) -> bool {
+    let t = Instant::now();
+    let ok = lv_prechecks(vk, dg, pi);
+    m.on_verify_stage("precheck", t.elapsed());
+    if !ok {
         return false;
     }
 
-    // Optional: keep the original gadgets as safety checks in debug builds
+    let t = Instant::now();
+    let shape = dg.linear_shape();
+    m.on_verify_stage("shape", t.elapsed());
+
+    let t = Instant::now();
+    let coords = build_lv_coords(vk, dg, pi);
+    m.on_verify_stage("coords", t.elapsed());
+    let Some(coords) = coords else {
+        return false;
+    };
+
+    let t = Instant::now();
+    let ok = recover_sb_via_linear_check(&shape, &coords.0);
+    m.on_verify_stage("linear_check", t.elapsed());
+    ok
+}
+
+/// Everything `lv_verify` checks before the linear system.
+fn lv_prechecks<E: Pairing>(vk: &VerifierKey<E>, dg: &LVDigest<E>, pi: &LVProofRefs<'_, E>) -> bool {
+    // Structural checks first, before any pairing.
+    if pi.precheck(dg).is_err() {
+        return false;
+    }
+
+    // The LV system only sees v_g1 of the x and y IIP proofs (c18, c19);
+    // their quotient equations tie v to the witness and must hold in every
+    // build.
+    if !iip_verify(&dg.iip_x, pi.iip_x) { return false; }
+    if !iip_verify(&dg.iip_y, pi.iip_y) { return false; }
+
+    // iip_z and NonZero are fully covered by rows 0-3; re-check them in
+    // debug builds as a safety net
     #[cfg(debug_assertions)]
     {
-        if !iip_verify(&dg.iip_x, &pi.iip_x) { return false; }
-        if !iip_verify(&dg.iip_y, &pi.iip_y) { return false; }
-        if !iip_verify(&dg.iip_z, &pi.iip_z) { return false; }
-        if !nonzero_verify(crs, &pi.nz, dg.one_idx) { return false; }
+        if !iip_verify(&dg.iip_z, pi.iip_z) { return false; }
+        if !nonzero_verify_vk(vk, pi.nz, dg.one_idx) { return false; }
     }
 
-    let shape = dg.linear_shape(crs);
-    let coords = match build_lv_coords(crs, dg, pi) {
-        Some(c) => c,
-        None => return false,
-    };
+    true
+}
+
+/// `lv_verify` plus the number of pairings it took: the gadget checks in
+/// `lv_prechecks`, one per GT coordinate (LV_NUM_COORDS) and two for the
+/// shape's RHS constants. The total is lower in release builds, which skip
+/// the IIP z and NonZero re-checks.
+pub fn lv_verify_counted<E: Pairing>(crs: &CRS<E>, dg: &LVDigest<E>, pi: &LVProof<E>) -> (bool, usize) {
+    PairingCounter::reset();
+    let ok = lv_verify(crs, dg, pi);
+    (ok, PairingCounter::get())
+}
+
+fn counted_pairing_prepared<E: Pairing>(a: E::G1, b: &E::G2Prepared) -> E::TargetField {
+    PAIRINGS.with(|c| c.set(c.get() + 1));
+    E::pairing(a, b.clone()).0
+}
+
+/// An `LVDigest` with everything proof-independent done once: the Miller
+/// loop line functions of its fixed G2 elements (g2, [τ]_2, [Z(τ)]_2,
+/// [τ^{N-n+1}]_2, [τ^N]_2, [Z_mul(τ)]_2, [τ - d]_2) and the linear shape,
+/// whose b_LV costs two pairings. 17 of the 20 coordinates then skip the
+/// G2 line computations; final exponentiations are unchanged (see the
+/// `lv_verify_prepared` bench).
+#[allow(non_snake_case)]
+pub struct PreparedLVDigest<E: Pairing = Bn254> {
+    pub dg: LVDigest<E>,
+    vk: VerifierKey<E>,
+    shape: LVShape<E>,
+    g2: E::G2Prepared,
+    tau_2: E::G2Prepared,
+    Z_tau_2: E::G2Prepared,
+    tau_N_minus_n_plus_1_2: E::G2Prepared,
+    tau_N_2: E::G2Prepared,
+    mul_z_tau_2: E::G2Prepared,
+    tau_minus_d_2: E::G2Prepared,
+}
+
+#[allow(non_snake_case)]
+impl<E: Pairing> PreparedLVDigest<E> {
+    pub fn new(crs: &CRS<E>, dg: &LVDigest<E>) -> Result<Self, DigestError> {
+        if dg.instance_deferred {
+            return Err(DigestError::InstanceDeferred);
+        }
+        for d in [&dg.iip_x, &dg.iip_y, &dg.iip_z] {
+            d.validate()?;
+        }
+        if dg.one_idx >= crs.n {
+            return Err(DigestError::OneIdxOutOfRange { one_idx: dg.one_idx, n: crs.n });
+        }
+        let vk = crs.verifier_key();
+        let tau_minus_d_2 = vk.tau_minus_d_2(dg.one_idx);
+        Ok(PreparedLVDigest {
+            dg: dg.clone(),
+            shape: dg.linear_shape(),
+            g2: E::G2::generator().into_affine().into(),
+            tau_2: dg.iip_z.tau_2.into_affine().into(),
+            Z_tau_2: dg.iip_z.Z_tau_2.into_affine().into(),
+            tau_N_minus_n_plus_1_2: dg.iip_z.tau_N_minus_n_plus_1_2.into_affine().into(),
+            tau_N_2: dg.iip_z.tau_N_2.into_affine().into(),
+            mul_z_tau_2: dg.mul_z_tau_2.into_affine().into(),
+            tau_minus_d_2: tau_minus_d_2.into_affine().into(),
+            vk,
+        })
+    }
+}
+
+/// `build_lv_coords` with the fixed G2 sides taken from `pdg`.
+fn build_lv_coords_prepared<E: Pairing>(pdg: &PreparedLVDigest<E>, pi: &LVProofRefs<'_, E>) -> Option<LVCoords<E>> {
+    let dg = &pdg.dg;
+    if pi.iip_z.w_tau_2 != pi.nz.w_tau_2 { return None; }
+
+    let g1 = E::G1::generator();
+    let y_inv = dg.iip_z.y_star.inverse()?;
+
+    Some(LVCoords([
+        counted_pairing::<E>(dg.iip_z.C, pi.iip_z.w_tau_2),
+        counted_pairing_prepared::<E>(pi.iip_z.v_g1.mul_bigint(y_inv.into_bigint()), &pdg.g2),
+        counted_pairing_prepared::<E>(pi.iip_z.QX_tau_1, &pdg.tau_2),
+        counted_pairing_prepared::<E>(pi.iip_z.QZ_tau_1, &pdg.Z_tau_2),
+        counted_pairing_prepared::<E>(pi.iip_z.QX_tau_1, &pdg.tau_N_minus_n_plus_1_2),
+        counted_pairing_prepared::<E>(pi.iip_z.QX_hat_tau_1, &pdg.g2),
+        counted_pairing_prepared::<E>(pi.iip_z.v_g1, &pdg.tau_N_2),
+        counted_pairing_prepared::<E>(pi.iip_z.v_hat_tau_1, &pdg.g2),
+        counted_pairing::<E>(g1, pi.nz.w_tau_2),
+        counted_pairing_prepared::<E>(pi.nz.q0_tau_1, &pdg.tau_minus_d_2),
+        counted_pairing_prepared::<E>(*pi.p_tau_1, &pdg.g2),
+        counted_pairing_prepared::<E>(*pi.h_tau_1, &pdg.mul_z_tau_2),
+        counted_pairing_prepared::<E>(*pi.a_tau_1, &pdg.g2),
+        counted_pairing_prepared::<E>(*pi.b_tau_1, &pdg.g2),
+        counted_pairing_prepared::<E>(pi.iip_z.v_g1, &pdg.g2),
+        counted_pairing_prepared::<E>(*pi.c_tau_1, &pdg.g2),
+        counted_pairing::<E>(dg.tau_N_minus_d_1, pi.iip_z.w_tau_2),
+        counted_pairing_prepared::<E>(*pi.w_hat_tau_1, &pdg.g2),
+        counted_pairing_prepared::<E>(pi.iip_x.v_g1, &pdg.g2),
+        counted_pairing_prepared::<E>(pi.iip_y.v_g1, &pdg.g2),
+    ]))
+}
+
+/// `lv_verify` against a `PreparedLVDigest`; same result, fewer Miller-loop
+/// steps and no per-call shape rebuild.
+pub fn lv_verify_prepared<E: Pairing>(pdg: &PreparedLVDigest<E>, pi: &LVProof<E>) -> bool {
+    let pi = pi.as_refs();
+    if !lv_prechecks(&pdg.vk, &pdg.dg, &pi) {
+        return false;
+    }
+    match build_lv_coords_prepared(pdg, &pi) {
+        Some(coords) => recover_sb_via_linear_check(&pdg.shape, &coords.0),
+        None => false,
+    }
+}
+
+/// Conjunction of several LV relations ("x*y=z AND a*b=c").
+/// The combined shape is block-diagonal: part k owns columns
+/// [k·LV_NUM_COORDS, (k+1)·LV_NUM_COORDS) and its own rows.
+#[derive(Clone)]
+pub struct ConjunctionDigest<E: Pairing = Bn254> {
+    pub parts: Vec<LVDigest<E>>,
+}
+
+impl<E: Pairing> ConjunctionDigest<E> {
+    /// Per-part shapes, stacked in part order.
+    pub fn linear_shapes(&self) -> Vec<LVShape<E>> {
+        self.parts.iter().map(|dg| dg.linear_shape()).collect()
+    }
+
+    /// Total number of GT coordinates over all parts.
+    pub fn num_coords(&self) -> usize {
+        self.parts.len() * LV_NUM_COORDS
+    }
+}
+
+/// A conjunction proof is one LVProof per part, in the same order.
+pub fn lv_verify_conjunction<E: Pairing>(crs: &CRS<E>, dg: &ConjunctionDigest<E>, pis: &[LVProof<E>]) -> bool {
+    if pis.len() != dg.parts.len() {
+        return false;
+    }
+    dg.parts.iter().zip(pis).all(|(d, pi)| lv_verify(crs, d, pi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::{MulWitness, bench_setup, mul_prove};
+    use ark_bn254::{Fq12, Fr};
+    use rand::Rng;
+
+    /// The proof's group elements in `transcript_bytes` order.
+    const FIELD_NAMES: [&str; 26] = [
+        "iip_x.w_tau_2", "iip_x.v_g1", "iip_x.QZ_tau_1", "iip_x.QX_tau_1", "iip_x.QX_hat_tau_1", "iip_x.v_hat_tau_1",
+        "iip_y.w_tau_2", "iip_y.v_g1", "iip_y.QZ_tau_1", "iip_y.QX_tau_1", "iip_y.QX_hat_tau_1", "iip_y.v_hat_tau_1",
+        "iip_z.w_tau_2", "iip_z.v_g1", "iip_z.QZ_tau_1", "iip_z.QX_tau_1", "iip_z.QX_hat_tau_1", "iip_z.v_hat_tau_1",
+        "nz.q0_tau_1", "nz.w_tau_2",
+        "p_tau_1", "h_tau_1", "a_tau_1", "b_tau_1", "c_tau_1", "w_hat_tau_1",
+    ];
+
+    /// A clone of `pi` with group element `idx` (see `FIELD_NAMES`) shifted
+    /// by a random nonzero multiple of the generator.
+    fn corrupt_field<E: Pairing>(pi: &LVProof<E>, idx: usize) -> LVProof<E> {
+        let mut r = E::ScalarField::zero();
+        while r.is_zero() {
+            r = E::ScalarField::from(rand::rng().random::<u128>());
+        }
+        let d1 = E::G1::generator().mul_bigint(r.into_bigint());
+        let d2 = E::G2::generator().mul_bigint(r.into_bigint());
+
+        let mut out = pi.clone();
+        let iip = match idx / 6 {
+            0 => Some(&mut out.iip_x),
+            1 => Some(&mut out.iip_y),
+            2 => Some(&mut out.iip_z),
+            _ => None,
+        };
+        match (iip, idx % 6) {
+            (Some(pi), 0) => pi.w_tau_2 += d2,
+            (Some(pi), 1) => pi.v_g1 += d1,
+            (Some(pi), 2) => pi.QZ_tau_1 += d1,
+            (Some(pi), 3) => pi.QX_tau_1 += d1,
+            (Some(pi), 4) => pi.QX_hat_tau_1 += d1,
+            (Some(pi), _) => pi.v_hat_tau_1 += d1,
+            (None, _) => match idx {
+                18 => out.nz.q0_tau_1 += d1,
+                19 => out.nz.w_tau_2 += d2,
+                20 => out.p_tau_1 += d1,
+                21 => out.h_tau_1 += d1,
+                22 => out.a_tau_1 += d1,
+                23 => out.b_tau_1 += d1,
+                24 => out.c_tau_1 += d1,
+                25 => out.w_hat_tau_1 += d1,
+                _ => panic!("corrupt_field: idx={} out of range", idx),
+            },
+        }
+        out
+    }
+
+    #[test]
+    fn claimed_z_matches_the_instance() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert_eq!(pi.claimed_z(), Some(w.z));
+        assert_eq!(pi.claimed_z(), Some(dg.lv.instance_z));
+
+        // a w[2] the commitments do not open to
+        let mut forged = pi.clone();
+        forged.w[2] += Fr::from(1u64);
+        assert_eq!(forged.claimed_z(), None);
+
+        // a short witness, as from_bytes accepts
+        let mut short = pi.clone();
+        short.w.truncate(2);
+        assert_eq!(short.claimed_z(), None);
+        let short = LVProof::<Bn254>::from_bytes(&short.to_bytes(), crs.N).unwrap();
+        assert_eq!(short.claimed_z(), None);
+    }
+
+    #[test]
+    fn bound_outputs_reject_a_wrong_value() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+
+        // two public outputs: x as well as z
+        let dg_xz = dg.lv.clone().bind_outputs(Some(w.x), None);
+        assert!(lv_verify(&crs, &dg_xz, &pi));
+        let dg_xyz = dg.lv.clone().bind_outputs(Some(w.x), Some(w.y));
+        assert!(lv_verify(&crs, &dg_xyz, &pi));
+
+        // same z, different factorisation: x is wrong
+        let w2 = MulWitness { x: w.x * w.y, y: Fr::from(1u64), z: w.z };
+        let pi2 = mul_prove(&crs, &dg, &w2).lv;
+        assert!(lv_verify(&crs, &dg.lv, &pi2));
+        assert!(!lv_verify(&crs, &dg_xz, &pi2));
+
+        // the bound value itself is wrong
+        let dg_bad = dg.lv.clone().bind_outputs(None, Some(w.y + Fr::from(1u64)));
+        assert!(!lv_verify(&crs, &dg_bad, &pi));
+    }
+
+    #[test]
+    fn every_corrupted_field_is_rejected() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(lv_verify(&crs, &dg.lv, &pi));
+
+        for (idx, name) in FIELD_NAMES.iter().enumerate() {
+            let bad = corrupt_field(&pi, idx);
+            assert!(!lv_verify(&crs, &dg.lv, &bad), "corrupted {} accepted", name);
+        }
+    }
+    #[test]
+    fn layout_is_consistent_and_a_permutation_is_caught() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(lv_layout_consistent(&crs, &dg.lv, &pi));
+
+        let coords = build_lv_coords(&crs.verifier_key(), &dg.lv, &pi.as_refs()).unwrap();
+        let elems = build_proof_side_elems(&crs, &dg.lv, &pi).unwrap();
+        let shape = dg.lv.linear_shape();
+        let cols = dg.lv.column_metadata(&crs).unwrap();
+        assert!(columns_agree(&cols, &elems, &coords.0, &shape));
+
+        // QX ◦ [τ]_2 and QZ ◦ Z share a side, so only the pairing notices
+        let mut permuted = cols.clone();
+        permuted.swap(2, 3);
+        assert!(!columns_agree(&permuted, &elems, &coords.0, &shape));
+
+        let mut unused = dg.lv.linear_shape();
+        for row in unused.a.iter_mut() {
+            row[5] = 0;
+        }
+        assert!(!columns_agree(&cols, &elems, &coords.0, &unused));
+    }
 
-    recover_sb_via_linear_check(&shape, &coords.0)
-}
\ No newline at end of file
+    #[test]
+    fn verify_counts_one_pairing_per_coordinate_plus_the_rhs() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let vk = crs.verifier_key();
+        let count = |check: &dyn Fn() -> bool| {
+            PairingCounter::reset();
+            assert!(check());
+            PairingCounter::get()
+        };
+        // three IIP equations with 4 + 2 + 2 pairings; NonZero's one with 3
+        let iip = count(&|| iip_verify(&dg.lv.iip_x, &pi.iip_x));
+        let nz = count(&|| nonzero_verify_vk(&vk, &pi.nz, dg.lv.one_idx));
+        assert_eq!((iip, nz), (8, 3));
+
+        let rechecks = if cfg!(debug_assertions) { iip + nz } else { 0 };
+        assert_eq!(lv_verify_counted(&crs, &dg.lv, &pi), (true, 2 * iip + rechecks + LV_NUM_COORDS + 2));
+    }
+
+    #[test]
+    fn forged_witness_length_is_rejected_before_allocating() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let bytes = pi.to_bytes();
+        let back = LVProof::<Bn254>::from_bytes(&bytes, crs.N).unwrap();
+        assert_eq!(back.to_bytes(), bytes);
+
+        // the u64 prefix sits before the witness and the six trailing G1 commitments
+        let at = bytes.len() - (pi.w.len() + 6) * 32 - 8;
+        assert_eq!(u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()), pi.w.len() as u64);
+        let mut forged = bytes.clone();
+        forged[at..at + 8].copy_from_slice(&999_999_999u64.to_le_bytes());
+
+        assert_eq!(
+            LVProof::<Bn254>::from_bytes(&forged, crs.N).err(),
+            Some(DeserializeError::TooLarge { len: 999_999_999, max: crs.N })
+        );
+        // with no useful bound, the remaining-bytes check still refuses it
+        assert_eq!(LVProof::<Bn254>::from_bytes(&forged, usize::MAX).err(), Some(DeserializeError::Truncated));
+    }
+
+    /// A point on the BN254 twist outside the order-r subgroup.
+    fn off_subgroup_g2() -> ark_bn254::G2Projective {
+        use ark_bn254::{Fq2, G2Affine};
+        use ark_ec::AffineRepr;
+        (1u64..)
+            .filter_map(|i| G2Affine::get_point_from_x_unchecked(Fq2::from(i), false))
+            .find(|p| p.is_on_curve() && !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap()
+            .into_group()
+    }
+
+    #[test]
+    fn off_subgroup_g2_is_rejected() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(pi.check_subgroup());
+        assert!(lv_verify(&crs, &dg.lv, &pi));
+
+        let bad_w = off_subgroup_g2();
+        let mut bad = pi.clone();
+        // keep the IIP/NonZero commitments equal so only membership is at fault
+        bad.iip_z.w_tau_2 = bad_w;
+        bad.nz.w_tau_2 = bad_w;
+        assert!(!bad.check_subgroup());
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+
+        let mut bad = pi.clone();
+        bad.iip_x.w_tau_2 = bad_w;
+        assert!(!bad.check_subgroup());
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+    }
+
+    #[test]
+    fn verifier_key_agrees_with_the_full_crs() {
+        let (crs, dg, w) = bench_setup();
+        let vk = crs.verifier_key();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(lv_verify_with_vk(&vk, &dg.lv, &pi));
+        assert_eq!(lv_verify_with_vk(&vk, &dg.lv, &pi), lv_verify(&crs, &dg.lv, &pi));
+
+        for (idx, name) in FIELD_NAMES.iter().enumerate() {
+            let bad = corrupt_field(&pi, idx);
+            assert_eq!(
+                lv_verify_with_vk(&vk, &dg.lv, &bad),
+                lv_verify(&crs, &dg.lv, &bad),
+                "verdicts differ for corrupted {}", name
+            );
+        }
+    }
+
+    #[test]
+    fn packed_shape_round_trips() {
+        let (_, dg, _) = bench_setup();
+        let shape = dg.lv.linear_shape();
+        let packed = shape.pack();
+        assert_eq!(packed.len(), 8 + (shape.rows * LV_NUM_COORDS).div_ceil(4));
+
+        let back = LVShape::<Bn254>::unpack(&packed, shape.b).unwrap();
+        assert_eq!(back.rows, shape.rows);
+        assert_eq!(back.a, shape.a);
+        // the hashed context only sees the packed bytes, so they must be canonical
+        assert_eq!(back.pack(), packed);
+
+        assert!(LVShape::<Bn254>::unpack(&packed[..packed.len() - 1], shape.b).is_none());
+        let mut bad = packed.clone();
+        bad[8] |= 0b11;
+        assert!(LVShape::<Bn254>::unpack(&bad, shape.b).is_none());
+        let mut bad = packed.clone();
+        bad[0..4].copy_from_slice(&(LV_MAX_ROWS as u32 + 1).to_le_bytes());
+        assert!(LVShape::<Bn254>::unpack(&bad, shape.b).is_none());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn digest_from_parts_matches_setup_and_checks_the_maxdeg_base() {
+        let (crs, dg, w) = bench_setup();
+        let lv = &dg.lv;
+        let build = |tau_N_minus_d_1| {
+            LVDigest::new(
+                &crs, lv.iip_x.clone(), lv.iip_y.clone(), lv.iip_z.clone(), lv.one_idx,
+                lv.mul_z_tau_2, lv.instance_z, lv.d_bound, tau_N_minus_d_1,
+            )
+        };
+
+        let rebuilt = build(lv.tau_N_minus_d_1).unwrap();
+        assert_eq!(rebuilt.linear_shape().pack(), lv.linear_shape().pack());
+        assert_eq!(rebuilt.linear_shape().b, lv.linear_shape().b);
+        assert_eq!(rebuilt.tau_N_minus_d_1, lv.tau_N_minus_d_1);
+        assert!(lv_verify(&crs, &rebuilt, &mul_prove(&crs, &dg, &w).lv));
+
+        let wrong = crs._g1_tau_pow(crs.N - lv.d_bound + 1);
+        assert_eq!(build(wrong).err(), Some(DigestError::BadMaxDegBase));
+    }
+
+    #[test]
+    fn malformed_iip_digests_are_rejected_without_panicking() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+
+        for which in 0..3 {
+            let mut bad = dg.lv.clone();
+            let d = match which {
+                0 => &mut bad.iip_x,
+                1 => &mut bad.iip_y,
+                _ => &mut bad.iip_z,
+            };
+            d.y_star = Fr::from(0u64);
+            let tag = d.gadget_tag;
+            assert_eq!(d.validate().err(), Some(DigestError::ZeroYStar { which: tag }));
+            assert!(!crate::iip::iip_verify(d, &pi.iip_z));
+            assert!(!lv_verify(&crs, &bad, &pi));
+        }
+
+        let mut bad = dg.lv.clone();
+        bad.iip_z.n = bad.iip_z.N + 1;
+        assert!(matches!(bad.iip_z.validate(), Err(DigestError::DomainTooLarge { .. })));
+
+        let mut bad = dg.lv.clone();
+        bad.iip_x.Z_tau_2 = <Bn254 as Pairing>::G2::zero();
+        assert!(matches!(bad.iip_x.validate(), Err(DigestError::IdentityElement { field: "Z_tau_2", .. })));
+        assert!(!lv_verify(&crs, &bad, &pi));
+    }
+
+    #[test]
+    fn committed_instance_still_binds_z() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let r = Fr::from(0x1234_5678u64);
+
+        let committed = dg.lv.commit_instance(&crs, r);
+        assert!(committed.instance_z_commitment.is_some());
+        assert_eq!(committed.instance_z, Fr::from(0u64));
+
+        let opened = committed.open_instance(&crs, r);
+        assert!(lv_verify(&crs, &opened, &pi));
+        assert!(!lv_verify(&crs, &committed.open_instance(&crs, r + Fr::from(1u64)), &pi));
+
+        // a valid proof for another output does not satisfy the opened commitment
+        let w2 = MulWitness { x: Fr::from(3u64), y: Fr::from(5u64), z: Fr::from(15u64) };
+        let dg2 = crate::mul_snark::MulDigest::setup(&crs, w2.z);
+        let pi2 = mul_prove(&crs, &dg2, &w2).lv;
+        assert!(lv_verify(&crs, &dg2.lv, &pi2));
+        assert!(!lv_verify(&crs, &opened, &pi2));
+    }
+
+    #[test]
+    fn size_breakdown_sums_to_the_serialized_size() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let parts = pi.size_breakdown();
+        let names: Vec<_> = parts.iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, ["iip_x", "iip_y", "iip_z", "nonzero", "witness", "mul", "maxdeg"]);
+        assert_eq!(parts.iter().map(|(_, b)| b).sum::<usize>(), pi.to_bytes().len());
+        // one G2 and five G1 elements per IIP proof
+        assert_eq!(parts[0].1, 64 + 5 * 32);
+    }
+
+    #[test]
+    fn row_order_does_not_change_the_verdict() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let vk = crs.verifier_key();
+        let shape = dg.lv.linear_shape();
+        let reversed: Vec<usize> = (0..shape.rows).rev().collect();
+        let rotated: Vec<usize> = (0..shape.rows).map(|i| (i + 3) % shape.rows).collect();
+
+        for proof in [pi.clone(), corrupt_field(&pi, 24), corrupt_field(&pi, 21)] {
+            let coords = build_lv_coords(&vk, &dg.lv, &proof.as_refs()).unwrap();
+            let verdict = recover_sb_via_linear_check(&shape, &coords.0);
+            for perm in [&reversed, &rotated] {
+                let shuffled = shape.permute_rows(perm);
+                assert_eq!(shuffled.row("mul"), Some(perm.iter().position(|&j| j == shape.row("mul").unwrap()).unwrap()));
+                assert_eq!(recover_sb_via_linear_check(&shuffled, &coords.0), verdict);
+            }
+        }
+
+        // a duplicated tag is refused outright
+        let coords = build_lv_coords(&vk, &dg.lv, &pi.as_refs()).unwrap();
+        let mut dup = dg.lv.linear_shape();
+        dup.row_tags[1] = dup.row_tags[0];
+        assert!(!recover_sb_via_linear_check(&dup, &coords.0));
+    }
+
+    /// The linear check row by row, inverting every -1 entry where it occurs.
+    fn naive_linear_check(shape: &LVShape, coords: &[Fq12; LV_NUM_COORDS]) -> bool {
+        (0..shape.rows).all(|i| {
+            let mut lhs = Fq12::one();
+            for j in 0..LV_NUM_COORDS {
+                match shape.a[i][j] {
+                    1 => lhs *= &coords[j],
+                    -1 => lhs *= coords[j].inverse().unwrap(),
+                    _ => {}
+                }
+            }
+            lhs == shape.b[i]
+        })
+    }
+
+    #[test]
+    fn cached_inverse_check_matches_the_naive_one() {
+        // under --features parallel this compares the rayon path with the serial one
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let vk = crs.verifier_key();
+        let coords = build_lv_coords(&vk, &dg.lv, &pi.as_refs()).unwrap().0;
+
+        // a shape that negates one column in two rows, so its inverse is shared
+        let shape = dg.lv.linear_shape();
+        let j = (0..LV_NUM_COORDS).find(|&j| shape.a[0][j] == -1).unwrap();
+        let i = (1..shape.rows).find(|&i| shape.a[i][j] == 0).unwrap();
+        let mut shared = dg.lv.linear_shape();
+        shared.a[i][j] = -1;
+        shared.b[i] *= coords[j].inverse().unwrap();
+
+        for shape in [dg.lv.linear_shape(), shared] {
+            assert!(naive_linear_check(&shape, &coords));
+            assert!(recover_sb_via_linear_check(&shape, &coords));
+            for k in [0, j, LV_NUM_COORDS - 1] {
+                let mut bad = coords;
+                bad[k] *= bad[k];
+                assert_eq!(recover_sb_via_linear_check(&shape, &bad), naive_linear_check(&shape, &bad), "coord {}", k);
+            }
+        }
+    }
+
+    #[test]
+    fn identity_commitments_are_rejected_only_for_nonzero_slots() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(!pi.reject_degenerate());
+
+        // x = 0: a_tau_1 and iip_x.v_g1 are legitimately the identity
+        let zw = MulWitness { x: Fr::from(0u64), y: Fr::from(17u64), z: Fr::from(0u64) };
+        let zdg = crate::mul_snark::MulDigest::setup(&crs, zw.z);
+        let zpi = mul_prove(&crs, &zdg, &zw).lv;
+        assert!(zpi.a_tau_1.is_zero() && zpi.iip_x.v_g1.is_zero());
+        assert!(!zpi.reject_degenerate());
+        assert!(lv_verify(&crs, &zdg.lv, &zpi));
+
+        // ... but not for the nonzero slot x = 12
+        let mut bad = pi.clone();
+        bad.a_tau_1 = <Bn254 as Pairing>::G1::zero();
+        assert!(bad.reject_degenerate());
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+
+        let mut bad = pi.clone();
+        bad.nz.w_tau_2 = <Bn254 as Pairing>::G2::zero();
+        assert!(bad.reject_degenerate());
+        assert!(!lv_verify(&crs, &dg.lv, &bad));
+    }
+
+    #[test]
+    fn verify_refs_matches_lv_verify() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(lv_verify_refs(&crs, &dg.lv, &pi.as_refs()));
+        for idx in 0..FIELD_NAMES.len() {
+            let bad = corrupt_field(&pi, idx);
+            assert_eq!(lv_verify_refs(&crs, &dg.lv, &bad.as_refs()), lv_verify(&crs, &dg.lv, &bad), "{}", FIELD_NAMES[idx]);
+        }
+
+        // refs assembled from elements held apart, nz borrowed from a proof of 3·5 = 15
+        let ow = MulWitness { x: Fr::from(3u64), y: Fr::from(5u64), z: Fr::from(15u64) };
+        let other = mul_prove(&crs, &crate::mul_snark::MulDigest::setup(&crs, ow.z), &ow).lv;
+        let mixed = LVProofRefs { nz: &other.nz, ..pi.as_refs() };
+        assert!(!lv_verify_refs(&crs, &dg.lv, &mixed));
+        assert!(lv_verify_refs(&crs, &dg.lv, &LVProofRefs { nz: &pi.nz, ..mixed }));
+    }
+
+    #[test]
+    fn one_deferred_digest_serves_two_instances() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let w2 = MulWitness { x: Fr::from(3u64), y: Fr::from(5u64), z: Fr::from(15u64) };
+        let pi2 = mul_prove(&crs, &crate::mul_snark::MulDigest::setup(&crs, w2.z), &w2).lv;
+
+        let deferred = dg.lv.defer_instance();
+        assert!(lv_verify_instance(&crs, &deferred, &pi, w.z));
+        assert!(lv_verify_instance(&crs, &deferred, &pi2, w2.z));
+
+        // the proof's z must match the instance passed in
+        assert!(!lv_verify_instance(&crs, &deferred, &pi, w2.z));
+        assert!(!lv_verify_instance(&crs, &deferred, &pi2, w.z));
+        // and without an instance there is nothing to verify against
+        assert!(!lv_verify(&crs, &deferred, &pi));
+        assert_eq!(PreparedLVDigest::new(&crs, &deferred).err(), Some(DigestError::InstanceDeferred));
+    }
+
+    #[test]
+    fn transpose_apply_matches_a_hand_computation() {
+        let mut a = [[0i8; LV_NUM_COORDS]; LV_MAX_ROWS];
+        a[0][0] = 1;
+        a[0][1] = -1;
+        a[1][1] = 1;
+        a[1][2] = 1;
+        a[2][0] = -1;
+        a[2][19] = 1;
+        let shape: LVShape = LVShape { rows: 3, a, b: [Fq12::one(); LV_MAX_ROWS], row_tags: LV_ROW_TAGS };
+
+        let r = [Fr::from(2u64), Fr::from(3u64), Fr::from(5u64)];
+        let mut expected = vec![Fr::zero(); LV_NUM_COORDS];
+        expected[0] = Fr::from(2u64) - Fr::from(5u64); // +r0 - r2
+        expected[1] = Fr::from(3u64) - Fr::from(2u64); // -r0 + r1
+        expected[2] = Fr::from(3u64); // +r1
+        expected[19] = Fr::from(5u64); // +r2
+        assert_eq!(shape.transpose_apply(&r), expected);
+        assert_eq!(expected[0], -Fr::from(3u64));
+    }
+
+    #[test]
+    fn precheck_reports_each_structural_failure() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert_eq!(pi.precheck(&dg.lv), Ok(()));
+
+        assert_eq!(pi.precheck(&dg.lv.defer_instance()), Err(PrecheckError::InstanceDeferred));
+
+        let mut bad_dg = dg.lv.clone();
+        bad_dg.iip_y.y_star = Fr::zero();
+        assert_eq!(
+            pi.precheck(&bad_dg),
+            Err(PrecheckError::Digest(DigestError::ZeroYStar { which: bad_dg.iip_y.gadget_tag }))
+        );
+
+        let mut bad_dg = dg.lv.clone();
+        bad_dg.one_idx += crs.n;
+        assert_eq!(pi.precheck(&bad_dg), Err(PrecheckError::OneIdxOutOfRange { one_idx: bad_dg.one_idx, n: crs.n }));
+
+        let mut bad = pi.clone();
+        bad.w.push(Fr::zero());
+        assert_eq!(bad.precheck(&dg.lv), Err(PrecheckError::WitnessLength { got: 5 }));
+
+        let other = <Bn254 as Pairing>::G2::generator();
+        for which in ["iip_x", "iip_y", "nonzero"] {
+            let mut bad = pi.clone();
+            match which {
+                "iip_x" => bad.iip_x.w_tau_2 = other,
+                "iip_y" => bad.iip_y.w_tau_2 = other,
+                _ => bad.nz.w_tau_2 = other,
+            }
+            assert_eq!(bad.precheck(&dg.lv), Err(PrecheckError::WitnessCommitmentMismatch { which }));
+        }
+
+        // all four commitments equal, so only subgroup membership is at fault
+        let off = off_subgroup_g2();
+        let mut bad = pi.clone();
+        bad.iip_x.w_tau_2 = off;
+        bad.iip_y.w_tau_2 = off;
+        bad.iip_z.w_tau_2 = off;
+        bad.nz.w_tau_2 = off;
+        assert_eq!(bad.precheck(&dg.lv), Err(PrecheckError::NotInSubgroup));
+
+        let mut bad = pi.clone();
+        bad.b_tau_1 = <Bn254 as Pairing>::G1::zero();
+        assert_eq!(bad.precheck(&dg.lv), Err(PrecheckError::Degenerate));
+    }
+
+    #[test]
+    fn transcript_bytes_have_a_fixed_length_and_separate_proofs() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        // three IIP proofs (one G2, five G1), NonZero (G1 + G2), six Mul/MaxDeg G1s
+        let expected = 3 * (64 + 5 * 32) + (32 + 64) + 6 * 32;
+        assert_eq!(pi.transcript_bytes().len(), expected);
+        assert_eq!(pi.transcript_bytes(), pi.clone().transcript_bytes());
+
+        let mut others = Vec::new();
+        for (x, y) in [(3u64, 5u64), (0, 9)] {
+            let w2 = MulWitness { x: Fr::from(x), y: Fr::from(y), z: Fr::from(x * y) };
+            others.push(mul_prove(&crs, &crate::mul_snark::MulDigest::setup(&crs, w2.z), &w2).lv);
+        }
+        for other in &others {
+            // identities compress to the same width, so x = 0 keeps the length
+            assert_eq!(other.transcript_bytes().len(), expected);
+            assert_ne!(other.transcript_bytes(), pi.transcript_bytes());
+        }
+        assert_ne!(others[0].transcript_bytes(), others[1].transcript_bytes());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn proof_under_N_12_verifies_with_an_N_20_crs_from_the_same_tau() {
+        use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+        use rand::SeedableRng;
+        let crs_at = |seed: u8, N: usize| -> CRS {
+            let domain = GeneralEvaluationDomain::new(4).unwrap();
+            CRS::setup_with_degree(rand_chacha::ChaCha20Rng::from_seed([seed; 32]), domain, N)
+        };
+        let (small, big) = (crs_at(1, 12), crs_at(1, 20));
+        let w = MulWitness { x: Fr::from(12u64), y: Fr::from(17u64), z: Fr::from(204u64) };
+        let dg = crate::mul_snark::MulDigest::setup(&small, w.z);
+        let pi = mul_prove(&small, &dg, &w).lv;
+
+        assert_eq!(dg.lv.check_verifier_crs(&big), Ok(()));
+        assert!(lv_verify(&small, &dg.lv, &pi));
+        assert!(lv_verify(&big, &dg.lv, &pi));
+
+        // another τ, or a verifier CRS below the digest's N, is reported
+        let other = crs_at(2, 20);
+        assert_eq!(dg.lv.check_verifier_crs(&other), Err(DigestError::VerifierCrsMismatch { what: "[τ]_2" }));
+        assert!(!lv_verify(&other, &dg.lv, &pi));
+        let big_dg = crate::mul_snark::MulDigest::setup(&big, w.z);
+        assert_eq!(big_dg.lv.check_verifier_crs(&small), Err(DigestError::VerifierCrsMismatch { what: "degree N" }));
+    }
+
+    /// `tau_minus_d_2` reduces the slot mod n, so one_idx + n would pass the
+    /// NonZero row; precheck must reject it in release builds too.
+    #[test]
+    fn lv_verify_rejects_a_wrapped_one_idx() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        assert!(lv_verify(&crs, &dg.lv, &pi));
+
+        let mut wrapped = dg.lv.clone();
+        wrapped.one_idx = dg.lv.one_idx + crs.n;
+        assert!(!lv_verify(&crs, &wrapped, &pi));
+        assert_eq!(
+            PreparedLVDigest::new(&crs, &wrapped).err(),
+            Some(DigestError::OneIdxOutOfRange { one_idx: wrapped.one_idx, n: crs.n })
+        );
+    }
+
+    #[test]
+    fn prepared_verify_agrees_with_lv_verify() {
+        let (crs, dg, w) = bench_setup();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        let pdg = PreparedLVDigest::new(&crs, &dg.lv).unwrap();
+        assert!(lv_verify_prepared(&pdg, &pi));
+
+        for (idx, name) in FIELD_NAMES.iter().enumerate() {
+            let bad = corrupt_field(&pi, idx);
+            assert_eq!(
+                lv_verify_prepared(&pdg, &bad),
+                lv_verify(&crs, &dg.lv, &bad),
+                "verdicts differ for corrupted {}", name
+            );
+        }
+    }
+}
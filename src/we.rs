@@ -1,22 +1,169 @@
 //src/we.rs
-use aes_gcm::{AeadInPlace, Aes256Gcm, KeyInit, Nonce};
-use sha2::{Digest, Sha256};
-use ark_ff::{Field, PrimeField, Zero, One};
+use aes_gcm::aead::consts::{U12, U13, U14, U15, U16};
+use aes_gcm::aes::Aes256;
+use aes_gcm::{AeadInPlace, AesGcm, KeyInit, Nonce, TagSize};
+use ark_ff::{Field, PrimeField, One};
 use ark_bn254::{Fr, Fq12, G1Projective as G1, G2Projective as G2, Bn254};
 use ark_ec::pairing::Pairing;
 use ark_ec::PrimeGroup;
-use ark_serialize::CanonicalSerialize;
-use rand::Rng;
-use crate::verifier::{LVDigest, LVProof, LVShape, LV_NUM_COORDS, LVColMeta, ColSide, build_proof_side_elems};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use crate::verifier::{DigestError, LVDigest, LVProof, LVShape, LV_NUM_COORDS, LVColMeta, ColSide, ConjunctionDigest, ProofElem, build_proof_side_elems};
 use crate::scs::CRS;
+use crate::trivial::TrivialDigest;
+use crate::transcript::{CryptoHash, Transcript};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+/// A 32-byte AEAD key that is wiped when dropped. Every derived key
+/// (encryptor or decryptor side) is handed out as one, so it does not
+/// linger in freed memory once the caller is done; the AEAD helpers take it
+/// by reference to avoid unwiped copies. Best-effort: the AES key schedule
+/// is wiped too (aes-gcm's `zeroize` feature), but register and spilled
+/// copies made during use are beyond our control.
+pub type AeadKey = Zeroizing<[u8; 32]>;
 
 /// LV header containing ct1 = s·A in source groups
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum HeaderElem { G1(G1), G2(G2) }
 
 #[derive(Clone, Debug)]
 pub struct LVHeader {
     pub c1: Vec<HeaderElem>,
+    /// `header_context` of the encryptor's CRS and shape(s).
+    pub context: [u8; 32],
+}
+
+/// Header with the columns of each `SHARED_PROOF_COLS` group added into one
+/// element: column j of `LVHeader::c1` contributes to elems[map[j]], so the
+/// LV_NUM_COORDS columns of a relation become 16 elements.
+///
+/// The decryptor pairs every column of a group with the same proof element P,
+/// and e(P, H_a)·e(P, H_b) = e(P, H_a + H_b), so one pairing per element gives
+/// the same GT value. Columns that merely share a public base (e.g. the many
+/// `g2` columns) carry different exponents α_j against different proof
+/// elements and cannot be folded without revealing α. Built by
+/// `lv_make_compressed_header`; the KDF and AAD absorb `elems`, so a key and
+/// ciphertext made for one encoding do not open under the other.
+#[derive(Clone, Debug)]
+pub struct CompressedLVHeader {
+    pub elems: Vec<HeaderElem>,
+    pub map: Vec<usize>,
+    pub context: [u8; 32],
+}
+
+/// Every header element sits in the group its column expects.
+fn header_sides_match(c1: &[HeaderElem], cols: &[LVColMeta]) -> bool {
+    c1.iter().zip(cols).all(|(e, col)| {
+        matches!(
+            (col.side, e),
+            (ColSide::ProofG1PublicG2, HeaderElem::G2(_)) | (ColSide::ProofG2PublicG1, HeaderElem::G1(_))
+        )
+    })
+}
+
+impl LVHeader {
+    /// Structural pre-check, no pairings: one element per column, each in the
+    /// group `params.cols` expects on the header side.
+    pub fn validate(&self, params: &LVPublicLinearParams) -> bool {
+        self.c1.len() == LV_NUM_COORDS && header_sides_match(&self.c1, &params.cols)
+    }
+
+    /// `validate` for a conjunction header: `params.parts.len()` blocks of
+    /// LV_NUM_COORDS columns, block k checked against `params.parts[k]`.
+    pub fn validate_conjunction(&self, params: &LVConjunctionParams) -> bool {
+        self.c1.len() == params.parts.len() * LV_NUM_COORDS
+            && self
+                .c1
+                .chunks(LV_NUM_COORDS)
+                .zip(&params.parts)
+                .all(|(cols, part)| header_sides_match(cols, &part.cols))
+    }
+
+    /// The header was made under the decryptor's CRS and shape(s). Catches
+    /// a CRS/digest mix-up before any pairing, instead of as a bare tag
+    /// failure after key derivation.
+    pub fn check_context(&self, crs: &CRS, parts: &[&LVPublicLinearParams]) -> Result<(), KeyDerivError> {
+        self.check_context_with_hash::<Sha256>(crs, parts)
+    }
+
+    /// `check_context` for a header made under hash `H`; a header made
+    /// under another hash fails with `ContextMismatch`.
+    pub fn check_context_with_hash<H: CryptoHash>(
+        &self,
+        crs: &CRS,
+        parts: &[&LVPublicLinearParams],
+    ) -> Result<(), KeyDerivError> {
+        if self.context == header_context::<H>(crs, parts) {
+            Ok(())
+        } else {
+            Err(KeyDerivError::ContextMismatch)
+        }
+    }
+}
+
+/// Columns whose proof-side element is the same point: [B(τ)]_2 for c0, c8
+/// and c16 (the IIP and NonZero commitments must agree), Q_X for c2 and c4,
+/// and iip_z's v for c6 and c14.
+const SHARED_PROOF_COLS: [&[usize]; 3] = [&[0, 8, 16], &[2, 4], &[6, 14]];
+
+/// Compressed index of each column of one relation, numbered in column order.
+fn compression_map() -> [usize; LV_NUM_COORDS] {
+    let mut map = [usize::MAX; LV_NUM_COORDS];
+    let mut next = 0;
+    for j in 0..LV_NUM_COORDS {
+        if map[j] != usize::MAX {
+            continue;
+        }
+        let group = SHARED_PROOF_COLS.iter().find(|g| g.contains(&j)).copied().unwrap_or(&[]);
+        for &k in group.iter().chain([&j]) {
+            map[k] = next;
+        }
+        next += 1;
+    }
+    map
+}
+
+/// Elements per relation in a compressed header.
+const COMPRESSED_LEN: usize = LV_NUM_COORDS - 4;
+
+fn compress_columns(c1: &[HeaderElem]) -> Option<(Vec<HeaderElem>, Vec<usize>)> {
+    if !c1.len().is_multiple_of(LV_NUM_COORDS) {
+        return None;
+    }
+    let block = compression_map();
+    let mut elems = Vec::with_capacity(c1.len() / LV_NUM_COORDS * COMPRESSED_LEN);
+    let mut map = Vec::with_capacity(c1.len());
+    for (k, cols) in c1.chunks(LV_NUM_COORDS).enumerate() {
+        let mut out: [Option<HeaderElem>; COMPRESSED_LEN] = std::array::from_fn(|_| None);
+        for (j, e) in cols.iter().enumerate() {
+            let slot = &mut out[block[j]];
+            *slot = Some(match (slot.take(), e) {
+                (None, e) => e.clone(),
+                (Some(HeaderElem::G1(a)), HeaderElem::G1(b)) => HeaderElem::G1(a + b),
+                (Some(HeaderElem::G2(a)), HeaderElem::G2(b)) => HeaderElem::G2(a + b),
+                _ => return None,
+            });
+            map.push(k * COMPRESSED_LEN + block[j]);
+        }
+        elems.extend(out.into_iter().flatten());
+    }
+    Some((elems, map))
+}
+
+/// Hash of the hash name, the CRS fingerprint and every part's shape
+/// (A packed, and b).
+fn header_context<H: CryptoHash>(crs: &CRS, parts: &[&LVPublicLinearParams]) -> [u8; 32] {
+    let mut t = Transcript::<H>::new_with_hash(b"we-snark/header-context");
+    t.append_bytes(b"hash", H::NAME);
+    t.append_bytes(b"crs", &crs.fingerprint());
+    for p in parts {
+        t.append_bytes(b"shape", &p.shape.pack());
+        for b in &p.shape.b[..p.shape.rows] {
+            t.append_gt(b"b", b);
+        }
+    }
+    t.challenge_bytes(b"context")
 }
 
 /// Public parameters an encryptor will use.
@@ -25,106 +172,124 @@ pub struct LVPublicLinearParams {
     pub cols: [LVColMeta; LV_NUM_COORDS],
 }
 
-/// What the encryptor calls to obtain A_LV, b_LV.
-pub fn lv_public_linear_params(crs: &CRS, dg: &LVDigest) -> LVPublicLinearParams {
-    let shape = dg.linear_shape(crs);
-    let cols = dg.column_metadata(crs);
-    LVPublicLinearParams { shape, cols }
+/// What the encryptor calls to obtain A_LV, b_LV. Fails if `one_idx` is
+/// outside the domain or a column's public base does not match its
+/// orientation, which header construction would otherwise hit as a panic.
+pub fn lv_public_linear_params(crs: &CRS, dg: &LVDigest) -> Result<LVPublicLinearParams, DigestError> {
+    let shape = dg.linear_shape();
+    let cols = dg.column_metadata(crs)?;
+    check_columns(&cols)?;
+    Ok(LVPublicLinearParams { shape, cols })
+}
+
+fn check_columns(cols: &[LVColMeta]) -> Result<(), DigestError> {
+    for (col, meta) in cols.iter().enumerate() {
+        if !meta.validate() {
+            return Err(DigestError::MalformedColumn { col });
+        }
+    }
+    Ok(())
 }
 
 fn derive_alphas(shape: &LVShape, r: &[Fr]) -> [Fr; LV_NUM_COORDS] {
-    let mut alpha = [Fr::zero(); LV_NUM_COORDS];
-    for i in 0..shape.rows {
-        let ri = r[i];
-        for j in 0..LV_NUM_COORDS {
-            match shape.a[i][j] {
-                1  => { alpha[j] += ri; }
-                -1 => { alpha[j] -= ri; }
-                _  => {}
-            }
-        }
-    }
-    alpha
-}
-
-fn kdf_from_gt_with_ctx(gt: &Fq12, hdr: &LVHeader, crs: &CRS, shape: &LVShape) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    
-    // 1) GT element
-    let mut gt_bytes = Vec::new();
-    gt.serialize_compressed(&mut gt_bytes).unwrap();
-    hasher.update(&gt_bytes);
-    
-    // 2) CRS context
-    hasher.update(&crs.n.to_le_bytes());
-    hasher.update(&crs.N.to_le_bytes());
-    
-    // 3) Shape matrix
-    for i in 0..shape.rows {
-        for j in 0..LV_NUM_COORDS {
-            hasher.update(&[shape.a[i][j] as u8]);
-        }
-    }
-    for i in 0..shape.rows {
-        let mut b_bytes = Vec::new();
-        shape.b[i].serialize_compressed(&mut b_bytes).unwrap();
-        hasher.update(&b_bytes);
-    }
-    
-    // 4) Header elements
-    for elem in &hdr.c1 {
-        let mut bytes = Vec::new();
-        match elem {
-            HeaderElem::G1(g) => g.serialize_compressed(&mut bytes).unwrap(),
-            HeaderElem::G2(g) => g.serialize_compressed(&mut bytes).unwrap(),
+    shape
+        .transpose_apply(r)
+        .try_into()
+        .expect("transpose_apply returns LV_NUM_COORDS entries")
+}
+
+/// Scheme version absorbed first by the KDF and the AAD. Bump it whenever
+/// the column layout, equation set or context encoding changes: every key
+/// and AAD then changes with it, so mismatched implementations fail on an
+/// explicit version rather than on unexplained tag errors.
+pub const SCHEME_VERSION: &[u8] = b"we-snark-v1";
+
+/// Hash of the public encryption context minus the header: hash name, CRS
+/// sizes, shape matrices and column gadget tags (one block per relation).
+/// Computed once per encryption or decryption and absorbed by both the KDF
+/// and the AAD, so the shapes are serialized and hashed a single time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextDigest(pub [u8; 32]);
+
+impl ContextDigest {
+    pub fn new<H: CryptoHash>(crs: &CRS, parts: &[&LVPublicLinearParams]) -> Self {
+        let mut t = Transcript::<H>::new_with_hash(b"we-snark/context");
+        t.append_bytes(b"hash", H::NAME);
+        t.append_u64(b"crs.n", crs.n as u64);
+        t.append_u64(b"crs.N", crs.N as u64);
+
+        for part in parts {
+            let shape = &part.shape;
+            t.append_bytes(b"shape.a", &shape.pack());
+            for i in 0..shape.rows {
+                t.append_gt(b"shape.b", &shape.b[i]);
+            }
+            for col in &part.cols {
+                t.append_bytes(b"col.tag", col.tag.as_bytes());
+            }
         }
-        hasher.update(&bytes);
+        ContextDigest(t.challenge_bytes(b"context"))
     }
-    
-    let digest = hasher.finalize();
-    let mut key = [0u8; 32];
-    key.copy_from_slice(&digest);
-    key
 }
 
-// binding to ct
-fn compute_aad(crs: &CRS, shape: &LVShape, hdr: &LVHeader) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    
-    hasher.update(&crs.n.to_le_bytes());
-    hasher.update(&crs.N.to_le_bytes());
-    
-    for i in 0..shape.rows {
-        for j in 0..LV_NUM_COORDS {
-            hasher.update(&[shape.a[i][j] as u8]);
-        }
-    }
-    
-    for i in 0..shape.rows {
-        let mut b_bytes = Vec::new();
-        shape.b[i].serialize_compressed(&mut b_bytes).unwrap();
-        hasher.update(&b_bytes);
-    }
-    
-    for elem in &hdr.c1 {
-        let mut bytes = Vec::new();
+/// Absorb the context digest and the header elements exactly as sent.
+fn append_context<H: CryptoHash>(t: &mut Transcript<H>, ctx: &ContextDigest, elems: &[HeaderElem]) {
+    t.append_bytes(b"ctx", &ctx.0);
+    for elem in elems {
         match elem {
-            HeaderElem::G1(g) => g.serialize_compressed(&mut bytes).unwrap(),
-            HeaderElem::G2(g) => g.serialize_compressed(&mut bytes).unwrap(),
+            HeaderElem::G1(g) => t.append_g1(b"hdr.g1", g),
+            HeaderElem::G2(g) => t.append_g2(b"hdr.g2", g),
         }
-        hasher.update(&bytes);
     }
-    
-    hasher.finalize().to_vec()
 }
 
-/// Encryptor: sample r (kept secret), compute ct1 = s·A in groups, return (header, key=H(s·b))
-#[allow(non_snake_case)]
-pub fn lv_make_header<R: Rng + ?Sized>(
+/// Binding the header here rules out re-randomizing it in transit. Shifting
+/// r to r + δ with δ_i = 0 wherever b_i ≠ 1 keeps the GT secret Π b_i^{r_i}
+/// and changes every column touched by δ; moving a point between two columns
+/// that share a proof element keeps even the decryptor's pairing product.
+/// The key and AAD absorb every column as sent, so either header opens
+/// neither the original key nor the original ciphertext. That is the
+/// intended non-malleability; unlinkable relaying would need the header
+/// dropped from this context.
+fn kdf_from_gt_with_ctx(gt: &Fq12, hdr: &LVHeader, ctx: &ContextDigest) -> AeadKey {
+    kdf_from_gt_with_challenge::<Sha256>(gt, &hdr.c1, ctx, None)
+}
+
+/// `challenge = None` leaves the transcript, and so the key, unchanged.
+fn kdf_from_gt_with_challenge<H: CryptoHash>(
+    gt: &Fq12,
+    elems: &[HeaderElem],
+    ctx: &ContextDigest,
+    challenge: Option<&[u8; 32]>,
+) -> AeadKey {
+    let mut t = Transcript::<H>::new_with_hash(b"we-snark/kdf");
+    t.append_bytes(b"version", SCHEME_VERSION);
+    t.append_gt(b"kem", gt);
+    append_context(&mut t, ctx, elems);
+    if let Some(c) = challenge {
+        t.append_bytes(b"challenge", c);
+    }
+    Zeroizing::new(t.challenge_bytes(b"key"))
+}
+
+// binding to ct
+fn compute_aad(ctx: &ContextDigest, hdr: &LVHeader) -> Vec<u8> {
+    compute_aad_with_hash::<Sha256>(ctx, &hdr.c1)
+}
+
+fn compute_aad_with_hash<H: CryptoHash>(ctx: &ContextDigest, elems: &[HeaderElem]) -> Vec<u8> {
+    let mut t = Transcript::<H>::new_with_hash(b"we-snark/aad");
+    t.append_bytes(b"version", SCHEME_VERSION);
+    append_context(&mut t, ctx, elems);
+    t.challenge_bytes(b"aad").to_vec()
+}
+
+/// Sample r for one relation and return its header columns ct1 = s·A
+/// together with the KEM secret Π b_i^{r_i} in GT.
+fn lv_header_part<R: Rng + ?Sized>(
     params: &LVPublicLinearParams,
-    crs: &CRS,
     rng: &mut R,
-) -> (LVHeader, [u8; 32]) {
+) -> (Vec<HeaderElem>, Fq12) {
     let rows = params.shape.rows;
 
     // sample s = r (kept secret, not published)
@@ -135,8 +300,13 @@ pub fn lv_make_header<R: Rng + ?Sized>(
         r.push(Fr::from_le_bytes_mod_order(&buf));
     }
 
+    (lv_header_columns(params, &r), lv_kem_gt_enc(params, &r))
+}
+
+/// Header columns ct1 = s·A for a given r (one entry per shape row).
+fn lv_header_columns(params: &LVPublicLinearParams, r: &[Fr]) -> Vec<HeaderElem> {
     // α = A^T · r (field vector)
-    let alpha = derive_alphas(&params.shape, &r);
+    let alpha = derive_alphas(&params.shape, r);
 
     // ct1[j] = (public_base_j)^{α_j} in the appropriate source group
     let mut c1 = Vec::with_capacity(LV_NUM_COORDS);
@@ -153,18 +323,193 @@ pub fn lv_make_header<R: Rng + ?Sized>(
         }
     }
 
-    let hdr = LVHeader { c1 };
+    c1
+}
 
-    // s·b in GT for KEM key (kept secret), now with context binding
+/// The encryptor's KEM secret Π b_i^{r_i} in GT, before the KDF. `r` must
+/// have one entry per shape row. Exposed for auditing the bilinear
+/// reconstruction against `lv_kem_gt_dec`.
+#[allow(non_snake_case)]
+pub fn lv_kem_gt_enc(params: &LVPublicLinearParams, r: &[Fr]) -> Fq12 {
+    assert_eq!(r.len(), params.shape.rows, "lv_kem_gt_enc: need one r_i per shape row");
     let mut B = Fq12::one();
-    for i in 0..rows {
+    for i in 0..params.shape.rows {
         B *= params.shape.b[i].pow(r[i].into_bigint());
     }
-    let key = kdf_from_gt_with_ctx(&B, &hdr, crs, &params.shape);
+    B
+}
+
+/// Header for a caller-chosen r, so `lv_kem_gt_enc(params, r)` can be
+/// compared with the decryptor's value. Testing and auditing only: whoever
+/// knows r knows the key.
+pub fn lv_make_header_from_r(params: &LVPublicLinearParams, crs: &CRS, r: &[Fr]) -> LVHeader {
+    assert_eq!(r.len(), params.shape.rows, "lv_make_header_from_r: need one r_i per shape row");
+    LVHeader { c1: lv_header_columns(params, r), context: header_context::<Sha256>(crs, &[params]) }
+}
+
+/// The decryptor's reconstruction ∏_j e(proof_side_j, ct1[j]) before the KDF;
+/// equals `lv_kem_gt_enc` for the header's r iff the proof satisfies the shape.
+/// `None` under the same conditions `lv_key_from_header` errors on.
+pub fn lv_kem_gt_dec(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+) -> Option<Fq12> {
+    hdr.check_context(crs, &[params]).ok()?;
+    lv_gt_from_header_part(crs, dg, params, &hdr.c1, pi).ok()
+}
+
+/// Encryptor: sample r (kept secret), compute ct1 = s·A in groups, return (header, key=H(s·b))
+pub fn lv_make_header<R: Rng + ?Sized>(
+    params: &LVPublicLinearParams,
+    crs: &CRS,
+    rng: &mut R,
+) -> (LVHeader, AeadKey) {
+    lv_make_header_with_challenge(params, crs, None, rng)
+}
+
+/// `lv_make_header` with a caller-chosen challenge mixed into the KDF. The
+/// decryptor must present the same challenge, so a proof opens only the
+/// ciphertexts issued for that challenge.
+pub fn lv_make_header_with_challenge<R: Rng + ?Sized>(
+    params: &LVPublicLinearParams,
+    crs: &CRS,
+    challenge: Option<&[u8; 32]>,
+    rng: &mut R,
+) -> (LVHeader, AeadKey) {
+    lv_make_header_with_hash::<Sha256, R>(params, crs, challenge, rng)
+}
+
+/// `lv_make_header_with_challenge` with header context, KDF and (via
+/// `aead_encrypt_with_hash`) AAD all run on `H` instead of SHA-256.
+pub fn lv_make_header_with_hash<H: CryptoHash, R: Rng + ?Sized>(
+    params: &LVPublicLinearParams,
+    crs: &CRS,
+    challenge: Option<&[u8; 32]>,
+    rng: &mut R,
+) -> (LVHeader, AeadKey) {
+    let (c1, gt) = lv_header_part(params, rng);
+    let hdr = LVHeader { c1, context: header_context::<H>(crs, &[params]) };
+
+    // KEM key with context binding
+    let key = kdf_from_gt_with_challenge::<H>(&gt, &hdr.c1, &ContextDigest::new::<H>(crs, &[params]), challenge);
 
     (hdr, key)
 }
 
+/// `lv_make_header` emitting the compressed encoding, with the key bound to
+/// the compressed elements. Encrypt with `aead_encrypt_compressed`.
+pub fn lv_make_compressed_header<R: Rng + ?Sized>(
+    params: &LVPublicLinearParams,
+    crs: &CRS,
+    rng: &mut R,
+) -> (CompressedLVHeader, AeadKey) {
+    let (c1, gt) = lv_header_part(params, rng);
+    let (elems, map) = compress_columns(&c1).expect("lv_make_compressed_header: header columns fold by construction");
+    let hdr = CompressedLVHeader { elems, map, context: header_context::<Sha256>(crs, &[params]) };
+    let key = kdf_from_gt_with_challenge::<Sha256>(&gt, &hdr.elems, &ContextDigest::new::<Sha256>(crs, &[params]), None);
+    (hdr, key)
+}
+
+/// Deterministic `lv_make_header` driven by a ChaCha20 stream seeded with `seed`,
+/// so tests can pin exact header and key bytes.
+///
+/// Testing only: anyone who knows the seed knows r and hence the KEM key.
+/// Never use this to encrypt real data.
+pub fn lv_make_header_seeded(
+    params: &LVPublicLinearParams,
+    crs: &CRS,
+    seed: [u8; 32],
+) -> (LVHeader, AeadKey) {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    lv_make_header(params, crs, &mut rng)
+}
+
+/// Why the decryptor could not derive a key from a header and proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyDerivError {
+    /// Header carries the wrong number of columns.
+    HeaderLength { expected: usize, got: usize },
+    /// Conjunction: digests, params and proofs disagree in number of parts.
+    PartCount { expected: usize, got: usize },
+    /// The proof's IIP and NonZero witness commitments differ.
+    InconsistentProof,
+    /// Header or proof element of column `col` is in the wrong group for the
+    /// column's orientation.
+    OrientationMismatch { col: usize },
+    /// A compressed header's map or element count is not the canonical one.
+    BadCompression,
+    /// The header was made under a different CRS or shape.
+    ContextMismatch,
+    /// A `MultiHeader` has no recipient `idx`.
+    RecipientOutOfRange { idx: usize, count: usize },
+}
+
+impl std::fmt::Display for KeyDerivError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyDerivError::HeaderLength { expected, got } => {
+                write!(f, "header has {} columns, expected {}", got, expected)
+            }
+            KeyDerivError::PartCount { expected, got } => {
+                write!(f, "conjunction has {} parts, expected {}", got, expected)
+            }
+            KeyDerivError::InconsistentProof => {
+                write!(f, "proof witness commitments (IIP vs NonZero) differ")
+            }
+            KeyDerivError::OrientationMismatch { col } => {
+                write!(f, "column {}: header/proof groups do not match its orientation", col)
+            }
+            KeyDerivError::BadCompression => write!(f, "compressed header map or length is not canonical"),
+            KeyDerivError::ContextMismatch => write!(f, "header was made under a different CRS or shape"),
+            KeyDerivError::RecipientOutOfRange { idx, count } => {
+                write!(f, "recipient {} out of range for {} headers", idx, count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyDerivError {}
+
+/// Pair one relation's header columns with its proof-side elements:
+/// ∏_j e(proof_side_j, ct1[j]) = ∏_i b_i^{r_i} via bilinearity
+fn lv_gt_from_header_part(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    c1: &[HeaderElem],
+    pi: &LVProof,
+) -> Result<Fq12, KeyDerivError> {
+    if c1.len() != LV_NUM_COORDS {
+        return Err(KeyDerivError::HeaderLength { expected: LV_NUM_COORDS, got: c1.len() });
+    }
+
+    let proof_elems = build_proof_side_elems(crs, dg, pi).ok_or(KeyDerivError::InconsistentProof)?;
+
+    let mut acc = Fq12::one();
+    for j in 0..LV_NUM_COORDS {
+        acc *= pair_column(params.cols[j].side, &c1[j], &proof_elems[j])
+            .ok_or(KeyDerivError::OrientationMismatch { col: j })?;
+    }
+    Ok(acc)
+}
+
+/// e(proof element, header element) for a column of orientation `side`;
+/// `None` if either element is in the wrong group.
+fn pair_column(side: ColSide, h: &HeaderElem, p: &ProofElem) -> Option<Fq12> {
+    match (side, h, p) {
+        (ColSide::ProofG1PublicG2, HeaderElem::G2(hg2), ProofElem::G1(pg1)) => {
+            Some(<Bn254 as Pairing>::pairing(*pg1, *hg2).0)
+        }
+        (ColSide::ProofG2PublicG1, HeaderElem::G1(hg1), ProofElem::G2(pg2)) => {
+            Some(<Bn254 as Pairing>::pairing(*hg1, *pg2).0)
+        }
+        _ => None,
+    }
+}
+
 /// Decryptor: derive key by pairing ct1 with proof elements to compute s·b in GT
 pub fn lv_key_from_header(
     crs: &CRS,
@@ -172,74 +517,1340 @@ pub fn lv_key_from_header(
     params: &LVPublicLinearParams,
     hdr: &LVHeader,
     pi: &LVProof,
-) -> Option<[u8; 32]> {
-    if hdr.c1.len() != LV_NUM_COORDS { return None; }
+) -> Result<AeadKey, KeyDerivError> {
+    lv_key_from_header_with_challenge(crs, dg, params, hdr, pi, None)
+}
+
+/// Decryptor side of `lv_make_header_with_challenge`.
+pub fn lv_key_from_header_with_challenge(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    challenge: Option<&[u8; 32]>,
+) -> Result<AeadKey, KeyDerivError> {
+    lv_key_from_header_with_hash::<Sha256>(crs, dg, params, hdr, pi, challenge)
+}
+
+/// Decryptor side of `lv_make_header_with_hash`.
+pub fn lv_key_from_header_with_hash<H: CryptoHash>(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    challenge: Option<&[u8; 32]>,
+) -> Result<AeadKey, KeyDerivError> {
+    let ctx = ContextDigest::new::<H>(crs, &[params]);
+    lv_key_from_header_with_ctx::<H>(crs, dg, params, hdr, pi, challenge, &ctx)
+}
+
+/// `lv_key_from_header_with_hash` under a precomputed `ContextDigest`, so a
+/// decryptor can reuse it for the AAD.
+fn lv_key_from_header_with_ctx<H: CryptoHash>(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    challenge: Option<&[u8; 32]>,
+    ctx: &ContextDigest,
+) -> Result<AeadKey, KeyDerivError> {
+    hdr.check_context_with_hash::<H>(crs, &[params])?;
+    let acc = lv_gt_from_header_part(crs, dg, params, &hdr.c1, pi)?;
+    Ok(kdf_from_gt_with_challenge::<H>(&acc, &hdr.c1, ctx, challenge))
+}
 
-    let proof_elems = build_proof_side_elems(crs, dg, pi)?;
+/// Same as `lv_key_from_header` for a compressed single-relation header from
+/// `lv_make_compressed_header`: one pairing per element instead of per column.
+pub fn lv_key_from_compressed_header(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &CompressedLVHeader,
+    pi: &LVProof,
+) -> Result<AeadKey, KeyDerivError> {
+    lv_key_from_compressed_header_with_ctx(crs, dg, params, hdr, pi, &ContextDigest::new::<Sha256>(crs, &[params]))
+}
+
+fn lv_key_from_compressed_header_with_ctx(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &CompressedLVHeader,
+    pi: &LVProof,
+    ctx: &ContextDigest,
+) -> Result<AeadKey, KeyDerivError> {
+    let map = compression_map();
+    if hdr.map[..] != map[..] || hdr.elems.len() != COMPRESSED_LEN {
+        return Err(KeyDerivError::BadCompression);
+    }
+    if hdr.context != header_context::<Sha256>(crs, &[params]) {
+        return Err(KeyDerivError::ContextMismatch);
+    }
+    let proof_elems = build_proof_side_elems(crs, dg, pi).ok_or(KeyDerivError::InconsistentProof)?;
 
-    // Compute ∏_j e(proof_side_j, ct1[j]) = ∏_i b_i^{r_i} via bilinearity
     let mut acc = Fq12::one();
     for j in 0..LV_NUM_COORDS {
-        match (params.cols[j].side, &hdr.c1[j], &proof_elems[j]) {
-            (ColSide::ProofG1PublicG2, HeaderElem::G2(hg2), crate::verifier::ProofElem::G1(pg1)) => {
-                acc *= <Bn254 as Pairing>::pairing(*pg1, *hg2).0;
-            }
-            (ColSide::ProofG2PublicG1, HeaderElem::G1(hg1), crate::verifier::ProofElem::G2(pg2)) => {
-                acc *= <Bn254 as Pairing>::pairing(*hg1, *pg2).0;
+        // the rest of the group shares column j's proof element
+        if map[..j].contains(&map[j]) {
+            continue;
+        }
+        acc *= pair_column(params.cols[j].side, &hdr.elems[map[j]], &proof_elems[j])
+            .ok_or(KeyDerivError::OrientationMismatch { col: j })?;
+    }
+    Ok(kdf_from_gt_with_challenge::<Sha256>(&acc, &hdr.elems, ctx, None))
+}
+
+/// `decrypt_with_lv_header` for a compressed header.
+pub fn decrypt_with_compressed_header(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &CompressedLVHeader,
+    pi: &LVProof,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    let ctx = ContextDigest::new::<Sha256>(crs, &[params]);
+    let key = lv_key_from_compressed_header_with_ctx(crs, dg, params, hdr, pi, &ctx)?;
+    let aad = compute_aad_with_hash::<Sha256>(&ctx, &hdr.elems);
+    aead_decrypt(&key[..], nonce, ct, tag, DEFAULT_TAG_LEN, &aad)?;
+    Ok(ct.to_vec())
+}
+
+/// Encryptor parameters for a `TrivialDigest`: its reduced shape with the
+/// full column table. Decrypt with `dg.lv` as the digest. Testing only:
+/// anyone can satisfy the trivial relation, so these headers protect
+/// nothing; their context keeps them from opening Mul headers.
+pub fn trivial_public_linear_params(crs: &CRS, dg: &TrivialDigest) -> Result<LVPublicLinearParams, DigestError> {
+    let cols = dg.lv.column_metadata(crs)?;
+    check_columns(&cols)?;
+    Ok(LVPublicLinearParams { shape: dg.linear_shape(), cols })
+}
+
+/// Public parameters for a conjunction: one block per part, in part order.
+pub struct LVConjunctionParams {
+    pub parts: Vec<LVPublicLinearParams>,
+}
+
+impl LVConjunctionParams {
+    fn part_refs(&self) -> Vec<&LVPublicLinearParams> {
+        self.parts.iter().collect()
+    }
+}
+
+pub fn lv_conjunction_params(crs: &CRS, dg: &ConjunctionDigest) -> Result<LVConjunctionParams, DigestError> {
+    let parts = dg.parts.iter().map(|d| lv_public_linear_params(crs, d)).collect::<Result<_, _>>()?;
+    Ok(LVConjunctionParams { parts })
+}
+
+/// Encryptor for a conjunction: independent r per part, header columns
+/// concatenated, key = H(Π_k s_k·b_k). Recovering the key needs every part's proof.
+pub fn lv_make_conjunction_header<R: Rng + ?Sized>(
+    params: &LVConjunctionParams,
+    crs: &CRS,
+    rng: &mut R,
+) -> (LVHeader, AeadKey) {
+    let mut c1 = Vec::with_capacity(params.parts.len() * LV_NUM_COORDS);
+    let mut gt = Fq12::one();
+    for part in &params.parts {
+        let (cols, gt_k) = lv_header_part(part, rng);
+        c1.extend(cols);
+        gt *= gt_k;
+    }
+    let hdr = LVHeader { c1, context: header_context::<Sha256>(crs, &params.part_refs()) };
+    let key = kdf_from_gt_with_ctx(&gt, &hdr, &ContextDigest::new::<Sha256>(crs, &params.part_refs()));
+    (hdr, key)
+}
+
+/// Decryptor for a conjunction: `pis[k]` must be a proof for `dg.parts[k]`.
+pub fn lv_key_from_conjunction_header(
+    crs: &CRS,
+    dg: &ConjunctionDigest,
+    params: &LVConjunctionParams,
+    hdr: &LVHeader,
+    pis: &[LVProof],
+) -> Result<AeadKey, KeyDerivError> {
+    let ctx = ContextDigest::new::<Sha256>(crs, &params.part_refs());
+    lv_key_from_conjunction_header_with_ctx(crs, dg, params, hdr, pis, &ctx)
+}
+
+fn lv_key_from_conjunction_header_with_ctx(
+    crs: &CRS,
+    dg: &ConjunctionDigest,
+    params: &LVConjunctionParams,
+    hdr: &LVHeader,
+    pis: &[LVProof],
+    ctx: &ContextDigest,
+) -> Result<AeadKey, KeyDerivError> {
+    let k = params.parts.len();
+    for got in [dg.parts.len(), pis.len()] {
+        if got != k {
+            return Err(KeyDerivError::PartCount { expected: k, got });
+        }
+    }
+    if hdr.c1.len() != k * LV_NUM_COORDS {
+        return Err(KeyDerivError::HeaderLength { expected: k * LV_NUM_COORDS, got: hdr.c1.len() });
+    }
+    hdr.check_context(crs, &params.part_refs())?;
+
+    let mut acc = Fq12::one();
+    for (idx, cols) in hdr.c1.chunks(LV_NUM_COORDS).enumerate() {
+        // report columns relative to the whole concatenated header
+        acc *= lv_gt_from_header_part(crs, &dg.parts[idx], &params.parts[idx], cols, &pis[idx])
+            .map_err(|e| match e {
+                KeyDerivError::OrientationMismatch { col } => {
+                    KeyDerivError::OrientationMismatch { col: idx * LV_NUM_COORDS + col }
+                }
+                e => e,
+            })?;
+    }
+    Ok(kdf_from_gt_with_ctx(&acc, hdr, ctx))
+}
+
+/// One ciphertext for several recipients, each with their own relation.
+/// `headers[i]` encapsulates a KEM key k_i for recipient i's relation and
+/// `wrapped[i] = K ⊕ k_i` carries the shared AEAD key K under it (k_i is a
+/// fresh KDF output, so this is a one-time pad). Any one valid proof
+/// recovers K; the AAD binds `root`, so no header or wrapped key can be
+/// swapped or dropped without failing the tag.
+#[derive(Clone, Debug)]
+pub struct MultiHeader {
+    pub headers: Vec<LVHeader>,
+    pub wrapped: Vec<[u8; 32]>,
+}
+
+impl MultiHeader {
+    /// Hash over the per-recipient leaves H(header_i, wrapped_i), in order.
+    pub fn root(&self) -> [u8; 32] {
+        let mut t = Transcript::<Sha256>::new(b"we-snark/multi-header");
+        t.append_u64(b"count", self.headers.len() as u64);
+        for (hdr, wrapped) in self.headers.iter().zip(&self.wrapped) {
+            let mut leaf = Transcript::<Sha256>::new(b"we-snark/multi-header/leaf");
+            leaf.append_bytes(b"context", &hdr.context);
+            for elem in &hdr.c1 {
+                match elem {
+                    HeaderElem::G1(g) => leaf.append_g1(b"hdr.g1", g),
+                    HeaderElem::G2(g) => leaf.append_g2(b"hdr.g2", g),
+                }
             }
-            _ => return None,
+            leaf.append_bytes(b"wrapped", wrapped);
+            t.append_bytes(b"leaf", &leaf.challenge_bytes(b"leaf"));
         }
+        t.challenge_bytes(b"root")
     }
 
-    Some(kdf_from_gt_with_ctx(&acc, hdr, crs, &params.shape))
+    fn aad(&self) -> Vec<u8> {
+        let mut t = Transcript::<Sha256>::new(b"we-snark/multi-aad");
+        t.append_bytes(b"version", SCHEME_VERSION);
+        t.append_bytes(b"root", &self.root());
+        t.challenge_bytes(b"aad").to_vec()
+    }
 }
 
-pub fn decrypt_with_lv_header(
+fn xor_key(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    std::array::from_fn(|i| a[i] ^ b[i])
+}
+
+/// Encryptor for `MultiHeader`: one header per entry of `params`, all
+/// wrapping one fresh AEAD key, which is returned.
+pub fn lv_make_multi_header<R: Rng + ?Sized>(
+    crs: &CRS,
+    params: &[&LVPublicLinearParams],
+    rng: &mut R,
+) -> (MultiHeader, AeadKey) {
+    let key = Zeroizing::new(rng.random::<[u8; 32]>());
+    let mut headers = Vec::with_capacity(params.len());
+    let mut wrapped = Vec::with_capacity(params.len());
+    for p in params {
+        let (hdr, k_i) = lv_make_header(p, crs, rng);
+        headers.push(hdr);
+        wrapped.push(xor_key(&key, &k_i));
+    }
+    (MultiHeader { headers, wrapped }, key)
+}
+
+/// Recipient `idx` recovers the shared key with a proof for its relation.
+pub fn lv_key_from_multi_header(
     crs: &CRS,
     dg: &LVDigest,
     params: &LVPublicLinearParams,
-    hdr: &LVHeader,
+    mh: &MultiHeader,
+    idx: usize,
+    pi: &LVProof,
+) -> Result<AeadKey, KeyDerivError> {
+    let count = mh.headers.len();
+    if mh.wrapped.len() != count {
+        return Err(KeyDerivError::PartCount { expected: count, got: mh.wrapped.len() });
+    }
+    if idx >= count {
+        return Err(KeyDerivError::RecipientOutOfRange { idx, count });
+    }
+    let k_i = lv_key_from_header(crs, dg, params, &mh.headers[idx], pi)?;
+    Ok(Zeroizing::new(xor_key(&mh.wrapped[idx], &k_i)))
+}
+
+/// AEAD under the shared key with the AAD bound to `mh.root()`.
+pub fn aead_encrypt_multi(mh: &MultiHeader, key: &[u8; 32], nonce_12: [u8; 12], plaintext: &mut [u8]) -> Vec<u8> {
+    gcm_seal(key, nonce_12, &mh.aad(), plaintext, DEFAULT_TAG_LEN)
+}
+
+/// `decrypt_with_lv_header` for recipient `idx` of a `MultiHeader`.
+pub fn decrypt_with_multi_header(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    mh: &MultiHeader,
+    idx: usize,
     pi: &LVProof,
     nonce: [u8; 12],
-    ct: &mut Vec<u8>,
+    ct: &mut [u8],
     tag: &[u8],
-) -> Option<Vec<u8>> {
-    let key = lv_key_from_header(crs, dg, params, hdr, pi)?;
-    let aad = compute_aad(crs, &params.shape, hdr);
-    if aead_decrypt(key, nonce, ct, tag, &aad) {
-        Some(ct.clone())
-    } else {
-        None
+) -> Result<Vec<u8>, DecryptError> {
+    if mh.headers.get(idx).is_some_and(|hdr| !hdr.validate(params)) {
+        return Err(DecryptError::MalformedHeader);
     }
+    let key = lv_key_from_multi_header(crs, dg, params, mh, idx, pi)?;
+    aead_decrypt(&key[..], nonce, ct, tag, DEFAULT_TAG_LEN, &mh.aad())?;
+    Ok(ct.to_vec())
 }
 
-pub fn aead_encrypt(
+pub fn aead_encrypt_conjunction(
     crs: &CRS,
-    shape: &LVShape,
+    params: &LVConjunctionParams,
     hdr: &LVHeader,
-    key: [u8; 32],
+    key: &[u8; 32],
     nonce_12: [u8; 12],
-    plaintext: &mut Vec<u8>,
+    plaintext: &mut [u8],
 ) -> Vec<u8> {
-    let aad = compute_aad(crs, shape, hdr);
-    let cipher = Aes256Gcm::new(&key.into());
+    let aad = compute_aad(&ContextDigest::new::<Sha256>(crs, &params.part_refs()), hdr);
+    gcm_seal(key, nonce_12, &aad, plaintext, DEFAULT_TAG_LEN)
+}
+
+pub fn decrypt_with_conjunction_header(
+    crs: &CRS,
+    dg: &ConjunctionDigest,
+    params: &LVConjunctionParams,
+    hdr: &LVHeader,
+    pis: &[LVProof],
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    if !hdr.validate_conjunction(params) {
+        return Err(DecryptError::MalformedHeader);
+    }
+    let ctx = ContextDigest::new::<Sha256>(crs, &params.part_refs());
+    let key = lv_key_from_conjunction_header_with_ctx(crs, dg, params, hdr, pis, &ctx)?;
+    let aad = compute_aad(&ctx, hdr);
+    aead_decrypt(&key[..], nonce, ct, tag, DEFAULT_TAG_LEN, &aad)?;
+    Ok(ct.to_vec())
+}
+
+pub fn decrypt_with_lv_header(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    decrypt_with_lv_header_challenge(crs, dg, params, hdr, pi, None, nonce, ct, tag)
+}
+
+/// `decrypt_with_lv_header` for headers made with
+/// `lv_make_header_with_challenge`; a wrong challenge gives a wrong key and
+/// the tag check fails.
+pub fn decrypt_with_lv_header_challenge(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    challenge: Option<&[u8; 32]>,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    decrypt_with_lv_header_with_hash::<Sha256>(crs, dg, params, hdr, pi, challenge, nonce, ct, tag)
+}
+
+/// `decrypt_with_lv_header_challenge` for a ciphertext made with
+/// `lv_make_header_with_hash::<H>` and `aead_encrypt_with_hash::<H>`.
+pub fn decrypt_with_lv_header_with_hash<H: CryptoHash>(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    challenge: Option<&[u8; 32]>,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    if !hdr.validate(params) {
+        return Err(DecryptError::MalformedHeader);
+    }
+    let ctx = ContextDigest::new::<H>(crs, &[params]);
+    let key = lv_key_from_header_with_ctx::<H>(crs, dg, params, hdr, pi, challenge, &ctx)?;
+    let aad = compute_aad_with_hash::<H>(&ctx, &hdr.c1);
+    aead_decrypt(&key[..], nonce, ct, tag, DEFAULT_TAG_LEN, &aad)?;
+    Ok(ct.to_vec())
+}
+
+/// Full-length GCM tag, used by the header-level helpers.
+pub const DEFAULT_TAG_LEN: usize = 16;
+
+/// Shortest tag we accept. GCM forgery probability grows roughly as
+/// 2^-(8·tag_len) per attempt (worse for long messages), so anything below
+/// 96 bits is refused outright.
+pub const MIN_TAG_LEN: usize = 12;
+
+/// The tag length is appended to the AAD so a tag produced for one length
+/// cannot be truncated into a valid tag for a shorter one.
+fn aad_with_tag_len(aad: &[u8], tag_len: usize) -> Vec<u8> {
+    let mut out = aad.to_vec();
+    out.push(tag_len as u8);
+    out
+}
+
+fn gcm_seal_sized<T: TagSize>(key: &[u8; 32], nonce_12: [u8; 12], aad: &[u8], plaintext: &mut [u8]) -> Vec<u8> {
+    let cipher = AesGcm::<Aes256, U12, T>::new(key.into());
     let nonce: &Nonce<_> = (&nonce_12).into();
     cipher
-        .encrypt_in_place_detached(&nonce, &aad, plaintext)
+        .encrypt_in_place_detached(nonce, aad, plaintext)
         .unwrap()
         .to_vec()
 }
 
+fn gcm_open_sized<T: TagSize>(key: &[u8], nonce_12: [u8; 12], aad: &[u8], ciphertext: &mut [u8], tag: &[u8]) -> Result<(), AeadError> {
+    let cipher = AesGcm::<Aes256, U12, T>::new_from_slice(key).map_err(|_| AeadError::KeyInit)?;
+    let nonce: &Nonce<_> = (&nonce_12).into();
+    cipher
+        .decrypt_in_place_detached(nonce, aad, ciphertext, tag.into())
+        .map_err(|_| AeadError::TagMismatch)
+}
+
+fn gcm_seal(key: &[u8; 32], nonce_12: [u8; 12], aad: &[u8], plaintext: &mut [u8], tag_len: usize) -> Vec<u8> {
+    assert!(
+        (MIN_TAG_LEN..=16).contains(&tag_len),
+        "aead_encrypt: tag_len must be in {MIN_TAG_LEN}..=16, got {tag_len}"
+    );
+    let aad = aad_with_tag_len(aad, tag_len);
+    match tag_len {
+        12 => gcm_seal_sized::<U12>(key, nonce_12, &aad, plaintext),
+        13 => gcm_seal_sized::<U13>(key, nonce_12, &aad, plaintext),
+        14 => gcm_seal_sized::<U14>(key, nonce_12, &aad, plaintext),
+        15 => gcm_seal_sized::<U15>(key, nonce_12, &aad, plaintext),
+        _ => gcm_seal_sized::<U16>(key, nonce_12, &aad, plaintext),
+    }
+}
+
+/// Encrypt under the header context; returns a `tag_len`-byte tag
+/// (`DEFAULT_TAG_LEN` for the full tag). Panics if `tag_len` is outside
+/// `MIN_TAG_LEN..=16`.
+pub fn aead_encrypt(
+    crs: &CRS,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    key: &[u8; 32],
+    nonce_12: [u8; 12],
+    tag_len: usize,
+    plaintext: &mut [u8],
+) -> Vec<u8> {
+    aead_encrypt_with_hash::<Sha256>(crs, params, hdr, key, nonce_12, tag_len, plaintext)
+}
+
+/// `aead_encrypt` with the AAD hashed under `H`; pair it with a header from
+/// `lv_make_header_with_hash::<H>`.
+pub fn aead_encrypt_with_hash<H: CryptoHash>(
+    crs: &CRS,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    key: &[u8; 32],
+    nonce_12: [u8; 12],
+    tag_len: usize,
+    plaintext: &mut [u8],
+) -> Vec<u8> {
+    let aad = compute_aad_with_hash::<H>(&ContextDigest::new::<H>(crs, &[params]), &hdr.c1);
+    gcm_seal(key, nonce_12, &aad, plaintext, tag_len)
+}
+
+/// `aead_encrypt` under a header from `lv_make_compressed_header`.
+pub fn aead_encrypt_compressed(
+    crs: &CRS,
+    params: &LVPublicLinearParams,
+    hdr: &CompressedLVHeader,
+    key: &[u8; 32],
+    nonce_12: [u8; 12],
+    tag_len: usize,
+    plaintext: &mut [u8],
+) -> Vec<u8> {
+    let aad = compute_aad_with_hash::<Sha256>(&ContextDigest::new::<Sha256>(crs, &[params]), &hdr.elems);
+    gcm_seal(key, nonce_12, &aad, plaintext, tag_len)
+}
+
+/// Pad to a multiple of `block`: u32 LE length || plaintext || zeros.
+fn pad_plaintext(plaintext: &[u8], block: usize) -> Vec<u8> {
+    assert!(block > 0, "pad_plaintext: block must be non-zero");
+    let len = u32::try_from(plaintext.len()).expect("pad_plaintext: plaintext longer than u32::MAX");
+    let mut out = Vec::with_capacity((4 + plaintext.len()).next_multiple_of(block));
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(plaintext);
+    out.resize((4 + plaintext.len()).next_multiple_of(block), 0);
+    out
+}
+
+/// Inverse of `pad_plaintext`; None on a bad length prefix or non-zero padding.
+fn unpad_plaintext(padded: &[u8]) -> Option<Vec<u8>> {
+    let (len, rest) = padded.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    if len > rest.len() || rest[len..].iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(rest[..len].to_vec())
+}
+
+/// `aead_encrypt` over the padded plaintext, so the ciphertext length only
+/// reveals ⌈(4 + len) / block⌉. The length prefix sits inside the AEAD, so
+/// it is authenticated. Returns (ciphertext, tag).
+pub fn aead_encrypt_padded(
+    crs: &CRS,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    key: &[u8; 32],
+    nonce_12: [u8; 12],
+    plaintext: &[u8],
+    block: usize,
+) -> (Vec<u8>, Vec<u8>) {
+    let mut buf = pad_plaintext(plaintext, block);
+    let tag = aead_encrypt(crs, params, hdr, key, nonce_12, DEFAULT_TAG_LEN, &mut buf);
+    (buf, tag)
+}
+
+/// `decrypt_with_lv_header` for `aead_encrypt_padded` ciphertexts; the
+/// padding is stripped only after the tag has verified.
+pub fn decrypt_with_lv_header_padded(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    let padded = decrypt_with_lv_header(crs, dg, params, hdr, pi, nonce, ct, tag)?;
+    unpad_plaintext(&padded).ok_or(DecryptError::BadPadding)
+}
+
+/// `aead_encrypt` with the full tag appended: returns ciphertext || tag.
+pub fn aead_encrypt_combined(
+    crs: &CRS,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    key: &[u8; 32],
+    nonce_12: [u8; 12],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let mut out = plaintext.to_vec();
+    let tag = aead_encrypt(crs, params, hdr, key, nonce_12, DEFAULT_TAG_LEN, &mut out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// `decrypt_with_lv_header` for `aead_encrypt_combined` output; the last
+/// `DEFAULT_TAG_LEN` bytes are the tag. A buffer shorter than the tag fails
+/// with `AeadError::TagLength`.
+pub fn decrypt_with_lv_header_combined(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    nonce: [u8; 12],
+    combined: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    let Some(split) = combined.len().checked_sub(DEFAULT_TAG_LEN) else {
+        return Err(AeadError::TagLength { expected: DEFAULT_TAG_LEN, got: combined.len() }.into());
+    };
+    let (ct, tag) = combined.split_at(split);
+    decrypt_with_lv_header(crs, dg, params, hdr, pi, nonce, &mut ct.to_vec(), tag)
+}
+
+/// How a `HeaderSealer` picks the 96-bit AES-GCM nonce.
+///
+/// Reusing a nonce under the same key is catastrophic for AES-GCM: it leaks the
+/// XOR of the plaintexts and allows tag forgeries. A fresh header gives a fresh
+/// key, but several messages encrypted under one header must never share a nonce.
+/// The nonce state lives in the sealer, next to the key it is used with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonceStrategy {
+    /// Fresh random nonce per message; safe up to ~2^32 messages per key.
+    Random,
+    /// Nonce = [0; 4] || counter (big-endian u64), starting at 0 and only
+    /// ever increasing; the counter is also bound into the AAD.
+    Counter,
+}
+
+/// A header's AEAD key together with its nonce state. The sealer owns the
+/// key, so a counter cannot be restarted by pairing the key with a second
+/// strategy; use one sealer for all messages under one header.
+pub struct HeaderSealer {
+    key: AeadKey,
+    strategy: NonceStrategy,
+    next: u64,
+}
+
+impl HeaderSealer {
+    pub fn new(key: AeadKey, strategy: NonceStrategy) -> Self {
+        HeaderSealer { key, strategy, next: 0 }
+    }
+
+    /// `aead_encrypt` under the next nonce; returns (nonce, tag). GCM
+    /// authenticates the nonce itself, so a tampered nonce fails decryption.
+    /// Open `Random` output with `decrypt_with_lv_header` and `Counter`
+    /// output with `decrypt_with_lv_header_counter`.
+    pub fn seal<R: Rng + ?Sized>(
+        &mut self,
+        crs: &CRS,
+        params: &LVPublicLinearParams,
+        hdr: &LVHeader,
+        rng: &mut R,
+        plaintext: &mut [u8],
+    ) -> ([u8; 12], Vec<u8>) {
+        let aad = compute_aad(&ContextDigest::new::<Sha256>(crs, &[params]), hdr);
+        match self.strategy {
+            NonceStrategy::Random => {
+                let nonce = rng.random();
+                (nonce, gcm_seal(&self.key, nonce, &aad, plaintext, DEFAULT_TAG_LEN))
+            }
+            NonceStrategy::Counter => {
+                let ctr = self.next;
+                self.next = ctr.checked_add(1).expect("nonce counter exhausted");
+                let nonce = counter_nonce(ctr);
+                (nonce, gcm_seal(&self.key, nonce, &counter_aad(&aad, ctr), plaintext, DEFAULT_TAG_LEN))
+            }
+        }
+    }
+}
+
+fn counter_nonce(ctr: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&ctr.to_be_bytes());
+    nonce
+}
+
+fn counter_aad(aad: &[u8], ctr: u64) -> Vec<u8> {
+    let mut out = aad.to_vec();
+    out.extend_from_slice(b"ctr");
+    out.extend_from_slice(&ctr.to_be_bytes());
+    out
+}
+
+/// `decrypt_with_lv_header` for `NonceStrategy::Counter` output: the counter
+/// is read back from the nonce and must match the one bound into the AAD.
+pub fn decrypt_with_lv_header_counter(
+    crs: &CRS,
+    dg: &LVDigest,
+    params: &LVPublicLinearParams,
+    hdr: &LVHeader,
+    pi: &LVProof,
+    nonce: [u8; 12],
+    ct: &mut [u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    if !hdr.validate(params) {
+        return Err(DecryptError::MalformedHeader);
+    }
+    let ctx = ContextDigest::new::<Sha256>(crs, &[params]);
+    let key = lv_key_from_header_with_ctx::<Sha256>(crs, dg, params, hdr, pi, None, &ctx)?;
+    let ctr = u64::from_be_bytes(nonce[4..].try_into().unwrap());
+    let aad = counter_aad(&compute_aad(&ctx, hdr), ctr);
+    aead_decrypt(&key[..], nonce, ct, tag, DEFAULT_TAG_LEN, &aad)?;
+    Ok(ct.to_vec())
+}
+
+/// Why AEAD decryption failed. GCM cannot tell a wrong key from a corrupted
+/// ciphertext; both surface as `TagMismatch`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AeadError {
+    /// The key is not a valid AES-256 key (wrong length).
+    KeyInit,
+    /// `tag` is not `expected` bytes, or `expected` is outside `MIN_TAG_LEN..=16`.
+    TagLength { expected: usize, got: usize },
+    /// Authentication failed.
+    TagMismatch,
+}
+
+impl std::fmt::Display for AeadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AeadError::KeyInit => write!(f, "invalid AES-256-GCM key"),
+            AeadError::TagLength { expected, got } => {
+                write!(f, "tag has {} bytes, expected {}", got, expected)
+            }
+            AeadError::TagMismatch => write!(f, "authentication tag mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for AeadError {}
+
+/// Why a header-level decryption returned no plaintext.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// `LVHeader::validate` rejected the header.
+    MalformedHeader,
+    KeyDerivation(KeyDerivError),
+    Aead(AeadError),
+    /// The authenticated plaintext has an invalid length prefix.
+    BadPadding,
+}
+
+impl std::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::MalformedHeader => write!(f, "malformed header"),
+            DecryptError::KeyDerivation(e) => write!(f, "key derivation failed: {}", e),
+            DecryptError::Aead(e) => write!(f, "AEAD decryption failed: {}", e),
+            DecryptError::BadPadding => write!(f, "invalid plaintext padding"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+impl From<KeyDerivError> for DecryptError {
+    fn from(e: KeyDerivError) -> Self {
+        DecryptError::KeyDerivation(e)
+    }
+}
+
+impl From<AeadError> for DecryptError {
+    fn from(e: AeadError) -> Self {
+        DecryptError::Aead(e)
+    }
+}
+
+/// `TagLength` if `tag` is not exactly `tag_len` bytes or `tag_len` is below
+/// `MIN_TAG_LEN`; `KeyInit` if `key` is not 32 bytes; `TagMismatch` if
+/// authentication fails.
 pub fn aead_decrypt(
-    key: [u8; 32],
+    key: &[u8],
     nonce_12: [u8; 12],
-    ciphertext: &mut Vec<u8>,
+    ciphertext: &mut [u8],
     tag: &[u8],
+    tag_len: usize,
     aad: &[u8],
-) -> bool {
-    let cipher = Aes256Gcm::new(&key.into());
-    let nonce: &Nonce<_> = (&nonce_12).into();
-    cipher
-        .decrypt_in_place_detached(&nonce, aad, ciphertext, tag.into())
-        .is_ok()
-}
\ No newline at end of file
+) -> Result<(), AeadError> {
+    if tag.len() != tag_len || !(MIN_TAG_LEN..=16).contains(&tag_len) {
+        return Err(AeadError::TagLength { expected: tag_len, got: tag.len() });
+    }
+    let aad = aad_with_tag_len(aad, tag_len);
+    match tag_len {
+        12 => gcm_open_sized::<U12>(key, nonce_12, &aad, ciphertext, tag),
+        13 => gcm_open_sized::<U13>(key, nonce_12, &aad, ciphertext, tag),
+        14 => gcm_open_sized::<U14>(key, nonce_12, &aad, ciphertext, tag),
+        15 => gcm_open_sized::<U15>(key, nonce_12, &aad, ciphertext, tag),
+        _ => gcm_open_sized::<U16>(key, nonce_12, &aad, ciphertext, tag),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mul_snark::{MulDigest, bench_setup, mul_prove};
+
+    /// CRS, Mul digest, its encryptor params and a valid proof.
+    fn fixture() -> (CRS, MulDigest, LVPublicLinearParams, LVProof) {
+        let (crs, dg, w) = bench_setup();
+        let params = lv_public_linear_params(&crs, &dg.lv).unwrap();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        (crs, dg, params, pi)
+    }
+
+    #[test]
+    fn compressed_header_derives_the_same_key() {
+        let (crs, dg, params, pi) = fixture();
+        let (chdr, key) = lv_make_compressed_header(&params, &crs, &mut rand::rng());
+        assert_eq!(chdr.elems.len(), LV_NUM_COORDS - 4);
+        assert_eq!(chdr.map.len(), LV_NUM_COORDS);
+
+        let k_comp = lv_key_from_compressed_header(&crs, &dg.lv, &params, &chdr, &pi).unwrap();
+        assert_eq!(*k_comp, *key);
+
+        let mut ct = b"compressed".to_vec();
+        let tag = aead_encrypt_compressed(&crs, &params, &chdr, &key, [3; 12], DEFAULT_TAG_LEN, &mut ct);
+        let pt = decrypt_with_compressed_header(&crs, &dg.lv, &params, &chdr, &pi, [3; 12], &mut ct, &tag).unwrap();
+        assert_eq!(pt, b"compressed");
+
+        let mut bad = chdr.clone();
+        bad.map.swap(0, 1);
+        assert_eq!(
+            lv_key_from_compressed_header(&crs, &dg.lv, &params, &bad, &pi).err(),
+            Some(KeyDerivError::BadCompression)
+        );
+    }
+
+    /// Moving a point D between two columns of a shared-proof group keeps the
+    /// decryptor's GT value, but the KDF and AAD bind every column, so the
+    /// altered header neither derives the key nor opens the ciphertext.
+    #[test]
+    fn header_altered_within_a_group_fails_to_decrypt() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let mut ct = b"secret".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [4; 12], DEFAULT_TAG_LEN, &mut ct);
+
+        for group in SHARED_PROOF_COLS {
+            let (a, b) = (group[0], group[1]);
+            let mut moved = hdr.clone();
+            let (lo, hi) = moved.c1.split_at_mut(b);
+            match (&mut lo[a], &mut hi[0]) {
+                (HeaderElem::G1(x), HeaderElem::G1(y)) => {
+                    let d = crs.g1_pows[1];
+                    *x += d;
+                    *y -= d;
+                }
+                (HeaderElem::G2(x), HeaderElem::G2(y)) => {
+                    let d = crs.g2_pows[1];
+                    *x += d;
+                    *y -= d;
+                }
+                _ => panic!("group {group:?} mixes G1 and G2"),
+            }
+            assert_eq!(
+                lv_kem_gt_dec(&crs, &dg.lv, &params, &moved, &pi),
+                lv_kem_gt_dec(&crs, &dg.lv, &params, &hdr, &pi)
+            );
+            assert_ne!(*lv_key_from_header(&crs, &dg.lv, &params, &moved, &pi).unwrap(), *key);
+            let mut c = ct.clone();
+            assert!(decrypt_with_lv_header(&crs, &dg.lv, &params, &moved, &pi, [4; 12], &mut c, &tag).is_err());
+        }
+        let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [4; 12], &mut ct, &tag).unwrap();
+        assert_eq!(pt, b"secret");
+    }
+
+    /// Shifting r along the rows with b_i = 1 keeps the KEM secret for a valid
+    /// proof, yet the shifted header derives a different key and cannot open
+    /// the original ciphertext.
+    #[test]
+    fn rerandomized_header_opens_neither_key_nor_ciphertext() {
+        let (crs, dg, params, pi) = fixture();
+        let rows = params.shape.rows;
+        let r: Vec<Fr> = (0..rows as u64).map(|i| Fr::from(1000 + i)).collect();
+        let mut r2 = r.clone();
+        let mut shifted = 0;
+        for i in 0..rows {
+            if params.shape.b[i].is_one() {
+                r2[i] += Fr::from(7u64);
+                shifted += 1;
+            }
+        }
+        assert!(shifted > 0, "fixture has no row with b_i = 1");
+        assert_eq!(lv_kem_gt_enc(&params, &r), lv_kem_gt_enc(&params, &r2));
+
+        let hdr = lv_make_header_from_r(&params, &crs, &r);
+        let moved = lv_make_header_from_r(&params, &crs, &r2);
+        assert!(hdr.c1.iter().zip(&moved.c1).any(|(a, b)| a != b));
+        assert_eq!(
+            lv_kem_gt_dec(&crs, &dg.lv, &params, &moved, &pi),
+            lv_kem_gt_dec(&crs, &dg.lv, &params, &hdr, &pi)
+        );
+
+        let key = lv_key_from_header(&crs, &dg.lv, &params, &hdr, &pi).unwrap();
+        assert_ne!(*lv_key_from_header(&crs, &dg.lv, &params, &moved, &pi).unwrap(), *key);
+        let mut ct = b"relayed".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [5; 12], DEFAULT_TAG_LEN, &mut ct);
+        assert!(decrypt_with_lv_header(&crs, &dg.lv, &params, &moved, &pi, [5; 12], &mut ct.clone(), &tag).is_err());
+        let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [5; 12], &mut ct, &tag).unwrap();
+        assert_eq!(pt, b"relayed");
+    }
+
+    /// A second Mul relation (3·5 = 15) under `crs`, with a valid proof.
+    fn other_relation(crs: &CRS) -> (MulDigest, LVProof) {
+        let w = crate::mul_snark::MulWitness { x: Fr::from(3u64), y: Fr::from(5u64), z: Fr::from(15u64) };
+        let dg = MulDigest::setup(crs, w.z);
+        let pi = mul_prove(crs, &dg, &w).lv;
+        (dg, pi)
+    }
+
+    #[test]
+    fn conjunction_needs_a_valid_proof_for_every_part() {
+        let (crs, dg0, _, pi0) = fixture();
+        let (dg1, pi1) = other_relation(&crs);
+        let dg = ConjunctionDigest { parts: vec![dg0.lv.clone(), dg1.lv.clone()] };
+        let params = lv_conjunction_params(&crs, &dg).unwrap();
+
+        let (hdr, key) = lv_make_conjunction_header(&params, &crs, &mut rand::rng());
+        assert!(hdr.validate_conjunction(&params));
+        let mut ct = b"both".to_vec();
+        let tag = aead_encrypt_conjunction(&crs, &params, &hdr, &key, [5; 12], &mut ct);
+
+        let open = |pis: &[LVProof], hdr: &LVHeader| {
+            decrypt_with_conjunction_header(&crs, &dg, &params, hdr, pis, [5; 12], &mut ct.clone(), &tag)
+        };
+        assert_eq!(open(&[pi0.clone(), pi1.clone()], &hdr).unwrap(), b"both");
+        // a proof for the other relation in either slot does not open it
+        assert!(open(&[pi0.clone(), pi0.clone()], &hdr).is_err());
+        assert!(open(&[pi1.clone(), pi1.clone()], &hdr).is_err());
+        assert!(open(&[pi1.clone(), pi0.clone()], &hdr).is_err());
+
+        // a column of the second block in the wrong group
+        let mut bad = hdr.clone();
+        let j = LV_NUM_COORDS + params.parts[1].cols.iter().position(|c| matches!(c.side, ColSide::ProofG1PublicG2)).unwrap();
+        bad.c1[j] = HeaderElem::G1(G1::generator());
+        assert!(!bad.validate_conjunction(&params));
+        assert_eq!(open(&[pi0.clone(), pi1.clone()], &bad).err(), Some(DecryptError::MalformedHeader));
+
+        let mut short = hdr.clone();
+        short.c1.truncate(LV_NUM_COORDS);
+        assert_eq!(open(&[pi0, pi1], &short).err(), Some(DecryptError::MalformedHeader));
+    }
+
+    #[test]
+    fn either_recipient_opens_a_multi_header() {
+        let (crs, dg0, params0, pi0) = fixture();
+        let (dg1, pi1) = other_relation(&crs);
+        let params1 = lv_public_linear_params(&crs, &dg1.lv).unwrap();
+
+        let (mh, key) = lv_make_multi_header(&crs, &[&params0, &params1], &mut rand::rng());
+        let mut ct = b"for either".to_vec();
+        let tag = aead_encrypt_multi(&mh, &key, [6; 12], &mut ct);
+
+        let open = |mh: &MultiHeader, idx: usize, dg: &MulDigest, params: &LVPublicLinearParams, pi: &LVProof| {
+            decrypt_with_multi_header(&crs, &dg.lv, params, mh, idx, pi, [6; 12], &mut ct.clone(), &tag)
+        };
+        assert_eq!(open(&mh, 0, &dg0, &params0, &pi0).unwrap(), b"for either");
+        assert_eq!(open(&mh, 1, &dg1, &params1, &pi1).unwrap(), b"for either");
+        // the wrong relation's proof for a slot
+        assert!(open(&mh, 0, &dg0, &params0, &pi1).is_err());
+
+        let mut swapped = mh.clone();
+        swapped.wrapped.swap(0, 1);
+        for (idx, dg, params, pi) in [(0, &dg0, &params0, &pi0), (1, &dg1, &params1, &pi1)] {
+            assert_eq!(
+                open(&swapped, idx, dg, params, pi).err(),
+                Some(DecryptError::Aead(AeadError::TagMismatch))
+            );
+        }
+    }
+
+    /// `fixture` with the CRS drawn from a fixed ChaCha20 seed, so header
+    /// and key bytes are reproducible. The proof is still randomised.
+    fn seeded_fixture() -> (CRS, MulDigest, LVPublicLinearParams, LVProof) {
+        let crs: CRS = CRS::setup(ChaCha20Rng::from_seed([7; 32]), 4);
+        let w = crate::mul_snark::MulWitness { x: Fr::from(12u64), y: Fr::from(17u64), z: Fr::from(204u64) };
+        let dg = MulDigest::setup(&crs, w.z);
+        let params = lv_public_linear_params(&crs, &dg.lv).unwrap();
+        let pi = mul_prove(&crs, &dg, &w).lv;
+        (crs, dg, params, pi)
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// SHA-256 over the compressed encoding of every header column.
+    fn header_hash(hdr: &LVHeader) -> [u8; 32] {
+        let mut t = Transcript::<Sha256>::new(b"test/header");
+        for e in &hdr.c1 {
+            match e {
+                HeaderElem::G1(g) => t.append_serializable(b"g1", g),
+                HeaderElem::G2(g) => t.append_serializable(b"g2", g),
+            }
+        }
+        t.challenge_bytes(b"hash")
+    }
+
+    #[test]
+    fn seeded_header_bytes_are_pinned() {
+        let (crs, _, params, _) = seeded_fixture();
+        let (hdr, key) = lv_make_header_seeded(&params, &crs, [9; 32]);
+        assert_eq!(hex(&header_hash(&hdr)), "ed1c0e9a880e937187943495e00f0c06a13e6f410a2fde907c48d0750ce18d60");
+        assert_eq!(hex(&key[..]), "24b48c6d2dac4ad9a5b08c406133797b6b13bd5d8195b423da0cdeb219a14fa0");
+
+        let (again, _) = lv_make_header_seeded(&params, &crs, [9; 32]);
+        assert_eq!(again.c1, hdr.c1);
+        let (other, _) = lv_make_header_seeded(&params, &crs, [10; 32]);
+        assert_ne!(other.c1, hdr.c1);
+    }
+
+    /// Pinned for SCHEME_VERSION = "we-snark-v1" and the current KDF.
+    /// Bumping the version (or changing what the KDF absorbs) changes every
+    /// key: update the constant deliberately, never to make the test pass.
+    #[test]
+    fn v1_key_is_pinned() {
+        assert_eq!(SCHEME_VERSION, b"we-snark-v1");
+        let (crs, dg, params, pi) = seeded_fixture();
+        let (_, key) = lv_make_header_seeded(&params, &crs, [9; 32]);
+        assert_eq!(hex(&key[..]), "24b48c6d2dac4ad9a5b08c406133797b6b13bd5d8195b423da0cdeb219a14fa0");
+
+        let (hdr, _) = lv_make_header_seeded(&params, &crs, [9; 32]);
+        assert_eq!(*lv_key_from_header(&crs, &dg.lv, &params, &hdr, &pi).unwrap(), *key);
+    }
+
+    #[test]
+    fn shared_context_digest_leaves_key_and_aad_unchanged() {
+        let (crs, dg, params, pi) = seeded_fixture();
+        let (hdr, key) = lv_make_header_seeded(&params, &crs, [9; 32]);
+        let ctx = ContextDigest::new::<Sha256>(&crs, &[&params]);
+        assert_eq!(hex(&ctx.0), "075955aa3b131fe7ed6cd45bac48e96f1dde6ef8b3a3c6a065b95952e5660793");
+
+        // one digest reused for key and AAD gives what each computes on its own
+        let shared = lv_key_from_header_with_ctx::<Sha256>(&crs, &dg.lv, &params, &hdr, &pi, None, &ctx).unwrap();
+        assert_eq!(*shared, *key);
+        assert_eq!(*shared, *lv_key_from_header(&crs, &dg.lv, &params, &hdr, &pi).unwrap());
+        let aad = compute_aad(&ctx, &hdr);
+        assert_eq!(aad, compute_aad(&ContextDigest::new::<Sha256>(&crs, &[&params]), &hdr));
+        assert_eq!(hex(&aad), "7e71cdedf9e997e7dfee331d3a73c152dec716e7a08825bcbc41ef822dc053fd");
+
+        let mut ct = b"shared".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [2; 12], DEFAULT_TAG_LEN, &mut ct);
+        let mut opened = ct.clone();
+        assert_eq!(aead_decrypt(&key[..], [2; 12], &mut opened, &tag, DEFAULT_TAG_LEN, &aad), Ok(()));
+        assert_eq!(opened, b"shared");
+    }
+
+    /// Encrypt under hash `H`, then decrypt under `D`.
+    fn hash_round_trip<H: CryptoHash, D: CryptoHash>(
+        crs: &CRS,
+        dg: &MulDigest,
+        params: &LVPublicLinearParams,
+        pi: &LVProof,
+    ) -> Result<Vec<u8>, DecryptError> {
+        let (hdr, key) = lv_make_header_with_hash::<H, _>(params, crs, None, &mut rand::rng());
+        let mut ct = b"hashed".to_vec();
+        let tag = aead_encrypt_with_hash::<H>(crs, params, &hdr, &key, [4; 12], DEFAULT_TAG_LEN, &mut ct);
+        decrypt_with_lv_header_with_hash::<D>(crs, &dg.lv, params, &hdr, pi, None, [4; 12], &mut ct, &tag)
+    }
+
+    #[test]
+    fn each_hash_round_trips_and_cross_hash_fails() {
+        use sha3::Sha3_256;
+        let (crs, dg, params, pi) = fixture();
+        assert_eq!(hash_round_trip::<Sha256, Sha256>(&crs, &dg, &params, &pi).unwrap(), b"hashed");
+        assert_eq!(hash_round_trip::<Sha3_256, Sha3_256>(&crs, &dg, &params, &pi).unwrap(), b"hashed");
+
+        let mismatch = Some(DecryptError::KeyDerivation(KeyDerivError::ContextMismatch));
+        assert_eq!(hash_round_trip::<Sha256, Sha3_256>(&crs, &dg, &params, &pi).err(), mismatch);
+        assert_eq!(hash_round_trip::<Sha3_256, Sha256>(&crs, &dg, &params, &pi).err(), mismatch);
+    }
+
+    #[test]
+    fn truncated_tags_round_trip_and_wrong_lengths_are_refused() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, _) = lv_make_header(&params, &crs, &mut rand::rng());
+        let key = lv_key_from_header(&crs, &dg.lv, &params, &hdr, &pi).unwrap();
+        let aad = compute_aad(&ContextDigest::new::<Sha256>(&crs, &[&params]), &hdr);
+
+        let mut ct = b"short tag".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [2; 12], MIN_TAG_LEN, &mut ct);
+        assert_eq!(tag.len(), 12);
+        let mut pt = ct.clone();
+        aead_decrypt(&key[..], [2; 12], &mut pt, &tag, 12, &aad).unwrap();
+        assert_eq!(pt, b"short tag");
+
+        assert_eq!(
+            aead_decrypt(&key[..], [2; 12], &mut ct.clone(), &tag[..11], 12, &aad),
+            Err(AeadError::TagLength { expected: 12, got: 11 })
+        );
+        assert_eq!(
+            aead_decrypt(&key[..], [2; 12], &mut ct.clone(), &tag[..11], 11, &aad),
+            Err(AeadError::TagLength { expected: 11, got: 11 })
+        );
+        assert_eq!(
+            decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [2; 12], &mut ct.clone(), &tag).err(),
+            Some(DecryptError::Aead(AeadError::TagLength { expected: 16, got: 12 }))
+        );
+
+        // a full tag cut to 12 bytes is not the 12-byte tag: the length is in the AAD
+        let mut ct16 = b"short tag".to_vec();
+        let tag16 = aead_encrypt(&crs, &params, &hdr, &key, [2; 12], DEFAULT_TAG_LEN, &mut ct16);
+        assert_eq!(
+            aead_decrypt(&key[..], [2; 12], &mut ct16, &tag16[..12], 12, &aad),
+            Err(AeadError::TagMismatch)
+        );
+    }
+
+    #[test]
+    fn combined_buffer_round_trips_and_short_ones_fail() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let buf = aead_encrypt_combined(&crs, &params, &hdr, &key, [8; 12], b"combined");
+        assert_eq!(buf.len(), b"combined".len() + DEFAULT_TAG_LEN);
+
+        let open = |b: &[u8]| decrypt_with_lv_header_combined(&crs, &dg.lv, &params, &hdr, &pi, [8; 12], b);
+        assert_eq!(open(&buf).unwrap(), b"combined");
+        assert_eq!(
+            open(&buf[..DEFAULT_TAG_LEN - 1]).err(),
+            Some(DecryptError::Aead(AeadError::TagLength { expected: DEFAULT_TAG_LEN, got: DEFAULT_TAG_LEN - 1 }))
+        );
+        assert_eq!(
+            open(&[]).err(),
+            Some(DecryptError::Aead(AeadError::TagLength { expected: DEFAULT_TAG_LEN, got: 0 }))
+        );
+        // dropping a ciphertext byte shifts the tag boundary
+        assert_eq!(open(&buf[1..]).err(), Some(DecryptError::Aead(AeadError::TagMismatch)));
+    }
+
+    #[test]
+    fn padding_hides_the_plaintext_length() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let msgs: [&[u8]; 4] = [b"", b"a", b"a little longer", &[0xab; 27]];
+
+        let mut lens = Vec::new();
+        for (i, m) in msgs.iter().enumerate() {
+            let nonce = [i as u8; 12];
+            let (mut ct, tag) = aead_encrypt_padded(&crs, &params, &hdr, &key, nonce, m, 32);
+            lens.push(ct.len());
+            let pt = decrypt_with_lv_header_padded(&crs, &dg.lv, &params, &hdr, &pi, nonce, &mut ct, &tag).unwrap();
+            assert_eq!(pt, *m);
+        }
+        assert!(lens.iter().all(|&l| l == 32), "{:?}", lens);
+
+        // 29 bytes + the 4-byte prefix spill into a second block
+        let (ct, _) = aead_encrypt_padded(&crs, &params, &hdr, &key, [9; 12], &[0; 29], 32);
+        assert_eq!(ct.len(), 64);
+    }
+
+    #[test]
+    fn authenticated_bad_padding_is_reported() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let open = |mut ct: Vec<u8>, tag: &[u8]| {
+            decrypt_with_lv_header_padded(&crs, &dg.lv, &params, &hdr, &pi, [1; 12], &mut ct, tag)
+        };
+
+        // a length prefix beyond the buffer
+        let mut buf = pad_plaintext(b"pad", 16);
+        buf[..4].copy_from_slice(&100u32.to_le_bytes());
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut buf);
+        assert_eq!(open(buf, &tag).err(), Some(DecryptError::BadPadding));
+
+        // non-zero padding bytes
+        let mut buf = pad_plaintext(b"pad", 16);
+        *buf.last_mut().unwrap() = 1;
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut buf);
+        assert_eq!(open(buf, &tag).err(), Some(DecryptError::BadPadding));
+
+        // shorter than the prefix
+        let mut buf = vec![0u8; 3];
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut buf);
+        assert_eq!(open(buf, &tag).err(), Some(DecryptError::BadPadding));
+    }
+
+    #[test]
+    fn wrong_challenge_fails_decryption() {
+        let (crs, dg, params, pi) = fixture();
+        let challenge = [0x5a; 32];
+        let (hdr, key) = lv_make_header_with_challenge(&params, &crs, Some(&challenge), &mut rand::rng());
+        let mut ct = b"challenged".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [3; 12], DEFAULT_TAG_LEN, &mut ct);
+
+        let open = |c: Option<&[u8; 32]>| {
+            decrypt_with_lv_header_challenge(&crs, &dg.lv, &params, &hdr, &pi, c, [3; 12], &mut ct.clone(), &tag)
+        };
+        assert_eq!(open(Some(&challenge)).unwrap(), b"challenged");
+        let mismatch = Some(DecryptError::Aead(AeadError::TagMismatch));
+        assert_eq!(open(Some(&[0x5b; 32])).err(), mismatch);
+        assert_eq!(open(None).err(), mismatch);
+    }
+
+    #[test]
+    fn gadget_tags_separate_equal_shapes() {
+        let (crs, dg, params, _) = fixture();
+        let mut retagged = lv_public_linear_params(&crs, &dg.lv).unwrap();
+        let (from, to) = (retagged.cols[0].tag, "iip_x");
+        assert_ne!(from, to);
+        retagged.cols[0].tag = to;
+
+        let (hdr_a, key_a) = lv_make_header_seeded(&params, &crs, [1; 32]);
+        let (hdr_b, key_b) = lv_make_header_seeded(&retagged, &crs, [1; 32]);
+        // same numeric shape and randomness, so the same header columns...
+        assert_eq!(hdr_a.c1, hdr_b.c1);
+        // ...but the tags keep the keys apart
+        assert_ne!(*key_a, *key_b);
+    }
+
+    #[test]
+    fn swapped_column_group_is_reported_by_index() {
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, _) = lv_make_header(&params, &crs, &mut rand::rng());
+        for col in [0, 7, LV_NUM_COORDS - 1] {
+            let mut bad = hdr.clone();
+            bad.c1[col] = match bad.c1[col] {
+                HeaderElem::G1(_) => HeaderElem::G2(G2::generator()),
+                HeaderElem::G2(_) => HeaderElem::G1(G1::generator()),
+            };
+            assert_eq!(
+                lv_key_from_header(&crs, &dg.lv, &params, &bad, &pi).err(),
+                Some(KeyDerivError::OrientationMismatch { col })
+            );
+        }
+    }
+
+    #[test]
+    fn validate_rejects_a_g1_where_g2_is_expected() {
+        let (crs, _, params, _) = fixture();
+        let (hdr, _) = lv_make_header(&params, &crs, &mut rand::rng());
+        assert!(hdr.validate(&params));
+
+        let col = hdr.c1.iter().position(|e| matches!(e, HeaderElem::G2(_))).unwrap();
+        let mut bad = hdr.clone();
+        bad.c1[col] = HeaderElem::G1(G1::generator());
+        assert!(!bad.validate(&params));
+
+        let mut short = hdr.clone();
+        short.c1.pop();
+        assert!(!short.validate(&params));
+    }
+
+    #[test]
+    fn different_n_crs_reports_a_context_mismatch() {
+        let (crs, dg, params, pi) = seeded_fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let mut ct = b"context".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [4; 12], DEFAULT_TAG_LEN, &mut ct);
+
+        // same τ and domain, larger N: the proof still verifies under it
+        let wider: CRS = CRS::setup_with_degree(ChaCha20Rng::from_seed([7; 32]), crs.domain, crs.N + 4);
+        assert!(crate::verifier::lv_verify(&wider, &dg.lv, &pi));
+        assert_eq!(
+            decrypt_with_lv_header(&wider, &dg.lv, &params, &hdr, &pi, [4; 12], &mut ct.clone(), &tag).err(),
+            Some(DecryptError::KeyDerivation(KeyDerivError::ContextMismatch))
+        );
+        assert_eq!(hdr.check_context(&wider, &[&params]).err(), Some(KeyDerivError::ContextMismatch));
+        assert_eq!(
+            decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [4; 12], &mut ct.clone(), &tag).unwrap(),
+            b"context"
+        );
+    }
+
+    #[test]
+    fn aead_errors_tell_key_init_from_tag_mismatch() {
+        let key = [0x42; 32];
+        let mut ct = b"typed errors".to_vec();
+        let tag = gcm_seal(&key, [1; 12], b"aad", &mut ct, DEFAULT_TAG_LEN);
+        assert_eq!(aead_decrypt(&key, [1; 12], &mut ct.clone(), &tag, DEFAULT_TAG_LEN, b"aad"), Ok(()));
+
+        assert_eq!(
+            aead_decrypt(&key[..31], [1; 12], &mut ct.clone(), &tag, DEFAULT_TAG_LEN, b"aad"),
+            Err(AeadError::KeyInit)
+        );
+        let mut flipped = tag.clone();
+        flipped[0] ^= 1;
+        assert_eq!(
+            aead_decrypt(&key, [1; 12], &mut ct.clone(), &flipped, DEFAULT_TAG_LEN, b"aad"),
+            Err(AeadError::TagMismatch)
+        );
+
+        // and through the header layer
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let mut ct = b"typed errors".to_vec();
+        let mut tag = aead_encrypt(&crs, &params, &hdr, &key, [1; 12], DEFAULT_TAG_LEN, &mut ct);
+        tag[DEFAULT_TAG_LEN - 1] ^= 0x80;
+        assert_eq!(
+            decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [1; 12], &mut ct, &tag).err(),
+            Some(DecryptError::Aead(AeadError::TagMismatch))
+        );
+    }
+
+    #[test]
+    fn encryptor_and_decryptor_kem_values_agree() {
+        let (crs, dg, params, pi) = fixture();
+        let r: Vec<Fr> = (0..params.shape.rows as u64).map(|i| Fr::from(1000 + 7 * i)).collect();
+        let hdr = lv_make_header_from_r(&params, &crs, &r);
+        let enc = lv_kem_gt_enc(&params, &r);
+        assert_eq!(lv_kem_gt_dec(&crs, &dg.lv, &params, &hdr, &pi), Some(enc));
+
+        // a different r_i on a row with b_i != 1 gives a different B
+        let i = (0..params.shape.rows).find(|&i| !params.shape.b[i].is_one()).unwrap();
+        let mut r2 = r.clone();
+        r2[i] += Fr::from(1u64);
+        assert_ne!(lv_kem_gt_enc(&params, &r2), enc);
+
+        // a proof for another statement reconstructs something else
+        let (_, other) = other_relation(&crs);
+        assert_ne!(lv_kem_gt_dec(&crs, &dg.lv, &params, &hdr, &other), Some(enc));
+    }
+
+    #[test]
+    fn zeroizing_keys_round_trip_and_clear() {
+        use zeroize::Zeroize;
+        // both sides hand out the key as Zeroizing, so it is wiped on drop
+        fn wiped_on_drop(_: &Zeroizing<[u8; 32]>) {}
+
+        let (crs, dg, params, pi) = fixture();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rand::rng());
+        let dec_key = lv_key_from_header(&crs, &dg.lv, &params, &hdr, &pi).unwrap();
+        wiped_on_drop(&key);
+        wiped_on_drop(&dec_key);
+        assert_eq!(*key, *dec_key);
+
+        let mut ct = b"zeroized".to_vec();
+        let tag = aead_encrypt(&crs, &params, &hdr, &key, [6; 12], DEFAULT_TAG_LEN, &mut ct);
+        let pt = decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, [6; 12], &mut ct, &tag);
+        assert_eq!(pt.unwrap(), b"zeroized");
+
+        // what Drop runs: the bytes are cleared in place
+        let mut copy = key.clone();
+        assert_ne!(*copy, [0u8; 32]);
+        copy.zeroize();
+        assert_eq!(*copy, [0u8; 32]);
+    }
+
+    #[test]
+    fn malformed_column_base_is_an_error_not_a_panic() {
+        let (crs, dg, params, _) = fixture();
+        assert!(params.cols.iter().all(|c| c.validate()));
+        assert_eq!(check_columns(&params.cols), Ok(()));
+
+        for col in [0, 8, LV_NUM_COORDS - 1] {
+            let mut cols = dg.lv.column_metadata(&crs).unwrap();
+            let meta = &mut cols[col];
+            match meta.side {
+                ColSide::ProofG1PublicG2 => meta.g2_pub = None,
+                ColSide::ProofG2PublicG1 => meta.g2_pub = Some(G2::generator()),
+            }
+            assert!(!cols[col].validate());
+            assert_eq!(check_columns(&cols), Err(DigestError::MalformedColumn { col }));
+        }
+
+        let mut wrapped = dg.lv.clone();
+        wrapped.one_idx += crs.n;
+        assert_eq!(
+            lv_public_linear_params(&crs, &wrapped).err(),
+            Some(DigestError::OneIdxOutOfRange { one_idx: wrapped.one_idx, n: crs.n })
+        );
+    }
+
+    #[test]
+    fn counter_nonces_are_distinct_and_bound() {
+        let (crs, dg, params, pi) = fixture();
+        let mut rng = rand::rng();
+        let (hdr, key) = lv_make_header(&params, &crs, &mut rng);
+        let mut sealer = HeaderSealer::new(key, NonceStrategy::Counter);
+
+        let mut m1 = b"first".to_vec();
+        let mut m2 = b"second".to_vec();
+        let (n1, t1) = sealer.seal(&crs, &params, &hdr, &mut rng, &mut m1);
+        let (n2, t2) = sealer.seal(&crs, &params, &hdr, &mut rng, &mut m2);
+        assert_ne!(n1, n2);
+        assert_eq!(n1, counter_nonce(0));
+        assert_eq!(n2, counter_nonce(1));
+
+        let pt = decrypt_with_lv_header_counter(&crs, &dg.lv, &params, &hdr, &pi, n2, &mut m2.clone(), &t2).unwrap();
+        assert_eq!(pt, b"second");
+        // the plain AAD lacks the counter
+        assert!(decrypt_with_lv_header(&crs, &dg.lv, &params, &hdr, &pi, n1, &mut m1.clone(), &t1).is_err());
+        assert!(decrypt_with_lv_header_counter(&crs, &dg.lv, &params, &hdr, &pi, n1, &mut m1, &t1).is_ok());
+    }
+}